@@ -0,0 +1,60 @@
+//! Benchmarks on ActorParam files, i.e. the parameter archives found in actor
+//! packs. The main file is the largest AI program in the test data; the actor
+//! link is the small file every actor has.
+
+#![feature(test)]
+extern crate test;
+
+use roead::aamp::ParameterIO;
+use test::{black_box, Bencher};
+
+/// ActorParam AI program (`Actor/AIProgram/*.baiprog`).
+const FILE: &str = "test/aamp/AIProgram/Lizalfos_Ice.baiprog";
+/// ActorParam actor link (`Actor/ActorLink/*.bxml`).
+const ACTOR_LINK: &str = "test/aamp/GameRomHorse.bxml";
+
+fn load() -> (Vec<u8>, ParameterIO) {
+    load_file(FILE)
+}
+
+fn load_file(file: &str) -> (Vec<u8>, ParameterIO) {
+    let data = std::fs::read(file).expect("benchmark file should exist");
+    let pio = ParameterIO::from_binary(&data).expect("benchmark file should parse");
+    (data, pio)
+}
+
+#[bench]
+fn from_binary(b: &mut Bencher) {
+    let (data, _) = load();
+    b.iter(|| ParameterIO::from_binary(black_box(&data)).expect("benchmark file should parse"));
+}
+
+#[bench]
+fn from_binary_actor_link(b: &mut Bencher) {
+    let (data, _) = load_file(ACTOR_LINK);
+    b.iter(|| ParameterIO::from_binary(black_box(&data)).expect("benchmark file should parse"));
+}
+
+#[bench]
+fn to_binary(b: &mut Bencher) {
+    let (_, pio) = load();
+    b.iter(|| black_box(&pio).to_binary());
+}
+
+#[cfg(feature = "yaml")]
+#[bench]
+fn from_text(b: &mut Bencher) {
+    let text = load().1.to_text();
+    b.iter(|| ParameterIO::from_text(black_box(&text)).expect("benchmark text should parse"));
+}
+
+#[bench]
+fn iter_objects(b: &mut Bencher) {
+    let (_, pio) = load();
+    b.iter(|| {
+        black_box(&pio)
+            .iter_objects()
+            .map(|(_, obj)| obj.len())
+            .sum::<usize>()
+    });
+}
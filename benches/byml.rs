@@ -0,0 +1,25 @@
+#![feature(test)]
+extern crate test;
+
+use roead::{byml::Byml, Endian};
+use test::{black_box, Bencher};
+
+const FILE: &str = "test/byml/ActorInfo.product.byml";
+
+fn load() -> (Vec<u8>, Byml) {
+    let data = std::fs::read(FILE).expect("benchmark file should exist");
+    let byml = Byml::from_binary(&data).expect("benchmark file should parse");
+    (data, byml)
+}
+
+#[bench]
+fn from_binary(b: &mut Bencher) {
+    let (data, _) = load();
+    b.iter(|| Byml::from_binary(black_box(&data)).expect("benchmark file should parse"));
+}
+
+#[bench]
+fn to_binary(b: &mut Bencher) {
+    let (_, byml) = load();
+    b.iter(|| black_box(&byml).to_binary(Endian::Big));
+}
@@ -0,0 +1,179 @@
+use super::*;
+
+/// Names of the user parameters found in the `LinkTarget` object of an actor
+/// link, in the order they appear in game files.
+pub const ACTOR_LINK_USERS: &[&str] = &[
+    "AIProgramUser",
+    "AIScheduleUser",
+    "ASUser",
+    "AttentionUser",
+    "AwarenessUser",
+    "BoneControlUser",
+    "ActorCaptureUser",
+    "ChemicalUser",
+    "DamageParamUser",
+    "DropTableUser",
+    "ElinkUser",
+    "GParamUser",
+    "LifeConditionUser",
+    "LODUser",
+    "ModelUser",
+    "PhysicsUser",
+    "ProfileUser",
+    "RgBlendWeightUser",
+    "RgConfigListUser",
+    "RecipeUser",
+    "ShopDataUser",
+    "SlinkUser",
+    "UMiiUser",
+    "XlinkUser",
+];
+
+/// Typed wrapper around the [`ParameterIO`] of a BOTW actor link
+/// (`Actor/ActorLink/*.bxml`).
+///
+/// An actor link has a `LinkTarget` object holding the actor's display name,
+/// priority, scale, and the names of the resource files ("users") it uses,
+/// plus an optional `Tags` object with parameters named `Tag0`, `Tag1`, ...
+/// Actor links do not contain action lists; those live in the AI program
+/// (`.baiprog`) named by `AIProgramUser`.
+///
+/// The wrapped parameter IO is accessible through [`ResActor::as_pio`] and
+/// [`ResActor::into_inner`], so entries without a typed accessor can still be
+/// edited directly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResActor(ParameterIO);
+
+impl ResActor {
+    /// Wrap a parameter IO, which must contain a `LinkTarget` object.
+    pub fn new(pio: ParameterIO) -> Result<Self> {
+        if pio.object("LinkTarget").is_none() {
+            return Err(Error::InvalidData("Actor link missing LinkTarget object"));
+        }
+        Ok(Self(pio))
+    }
+
+    /// Parse an actor link from binary data.
+    pub fn from_binary(data: impl AsRef<[u8]>) -> Result<Self> {
+        Self::new(ParameterIO::from_binary(data)?)
+    }
+
+    /// Borrow the wrapped parameter IO.
+    pub fn as_pio(&self) -> &ParameterIO {
+        &self.0
+    }
+
+    /// Unwrap the parameter IO.
+    pub fn into_inner(self) -> ParameterIO {
+        self.0
+    }
+
+    fn link_target(&self) -> &ParameterObject {
+        &self.0.param_root.objects["LinkTarget"]
+    }
+
+    fn link_target_mut(&mut self) -> &mut ParameterObject {
+        &mut self.0.param_root.objects["LinkTarget"]
+    }
+
+    fn get_str(&self, name: &str) -> Option<&str> {
+        self.link_target().get(name).and_then(|p| p.as_str().ok())
+    }
+
+    /// The Japanese display name of the actor (`ActorNameJpn`).
+    pub fn actor_name_jpn(&self) -> Option<&str> {
+        self.get_str("ActorNameJpn")
+    }
+
+    /// The actor's load priority (`Priority`).
+    pub fn priority(&self) -> Option<&str> {
+        self.get_str("Priority")
+    }
+
+    /// The actor's scale (`ActorScale`).
+    pub fn actor_scale(&self) -> Option<f32> {
+        self.link_target()
+            .get("ActorScale")
+            .and_then(|p| p.as_f32().ok())
+    }
+
+    /// The resource name for a user parameter, e.g. `"PhysicsUser"`.
+    pub fn user(&self, name: &str) -> Option<&str> {
+        self.get_str(name)
+    }
+
+    /// Set the resource name for a user parameter. An existing parameter
+    /// keeps its string type; a new one is added as a string reference.
+    pub fn set_user(&mut self, name: &str, value: &str) {
+        let param = match self.link_target().get(name) {
+            Some(Parameter::String32(_)) => Parameter::String32(value.into()),
+            Some(Parameter::String64(_)) => Parameter::String64(Box::new(value.into())),
+            Some(Parameter::String256(_)) => Parameter::String256(Box::new(value.into())),
+            _ => Parameter::StringRef(value.into()),
+        };
+        self.link_target_mut().insert(name, param);
+    }
+
+    /// Iterate over the known user parameters present in this actor link, as
+    /// `(parameter name, resource name)` pairs in the order of
+    /// [`ACTOR_LINK_USERS`].
+    pub fn users(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        ACTOR_LINK_USERS
+            .iter()
+            .filter_map(|&name| self.get_str(name).map(|value| (name, value)))
+    }
+
+    /// Iterate over the actor's tags.
+    pub fn tags(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .object("Tags")
+            .into_iter()
+            .flat_map(|tags| tags.0.values())
+            .filter_map(|p| p.as_str().ok())
+    }
+}
+
+impl TryFrom<ParameterIO> for ResActor {
+    type Error = Error;
+
+    fn try_from(pio: ParameterIO) -> Result<Self> {
+        Self::new(pio)
+    }
+}
+
+impl From<ResActor> for ParameterIO {
+    fn from(actor: ResActor) -> Self {
+        actor.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actor_link() {
+        let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+        let mut actor = ResActor::from_binary(data).unwrap();
+        assert_eq!(actor.actor_name_jpn(), Some("ゲームROM馬"));
+        assert_eq!(actor.priority(), Some("Default"));
+        assert_eq!(actor.actor_scale(), Some(1.0));
+        assert_eq!(actor.user("PhysicsUser"), Some("EngineRom_Horse_Link"));
+        assert_eq!(actor.users().count(), ACTOR_LINK_USERS.len());
+        assert_eq!(actor.tags().collect::<Vec<_>>(), [
+            "CanRidden",
+            "UseAnimalUnit",
+            "ZukanAnimal"
+        ]);
+
+        actor.set_user("ModelUser", "Horse_Link_001");
+        assert_eq!(actor.user("ModelUser"), Some("Horse_Link_001"));
+        let pio: ParameterIO = actor.into();
+        assert_eq!(
+            pio.object("LinkTarget").unwrap().get("ModelUser"),
+            Some(&Parameter::StringRef("Horse_Link_001".into()))
+        );
+
+        assert!(ResActor::new(ParameterIO::new()).is_err());
+    }
+}
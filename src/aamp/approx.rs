@@ -0,0 +1,136 @@
+use super::*;
+
+/// Compare floats with an absolute tolerance. Infinities of the same sign,
+/// and NaNs, compare equal to each other.
+#[inline]
+fn float_approx_eq(a: f32, b: f32, epsilon: f32) -> bool {
+    a == b || (a - b).abs() <= epsilon || (a.is_nan() && b.is_nan())
+}
+
+fn floats_approx_eq(a: &[f32], b: &[f32], epsilon: f32) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| float_approx_eq(*a, *b, epsilon))
+}
+
+fn curves_approx_eq(a: &[Curve], b: &[Curve], epsilon: f32) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.a == b.a && a.b == b.b && floats_approx_eq(&a.floats, &b.floats, epsilon)
+        })
+}
+
+impl Parameter {
+    /// Compare two parameters, treating floats (including those in vectors,
+    /// colors, quaternions, curves and float buffers) as equal if they differ
+    /// by at most `epsilon`. Other types are compared exactly.
+    pub fn approx_eq(&self, other: &Parameter, epsilon: f32) -> bool {
+        use Parameter::*;
+        match (self, other) {
+            (F32(a), F32(b)) => float_approx_eq(*a, *b, epsilon),
+            (Vec2(a), Vec2(b)) => floats_approx_eq(&[a.x, a.y], &[b.x, b.y], epsilon),
+            (Vec3(a), Vec3(b)) => floats_approx_eq(&[a.x, a.y, a.z], &[b.x, b.y, b.z], epsilon),
+            (Vec4(a), Vec4(b)) => {
+                floats_approx_eq(&[a.x, a.y, a.z, a.t], &[b.x, b.y, b.z, b.t], epsilon)
+            }
+            (Color(a), Color(b)) => {
+                floats_approx_eq(&[a.r, a.g, a.b, a.a], &[b.r, b.g, b.b, b.a], epsilon)
+            }
+            (Quat(a), Quat(b)) => {
+                floats_approx_eq(&[a.a, a.b, a.c, a.d], &[b.a, b.b, b.c, b.d], epsilon)
+            }
+            (Curve1(a), Curve1(b)) => curves_approx_eq(&a[..], &b[..], epsilon),
+            (Curve2(a), Curve2(b)) => curves_approx_eq(&a[..], &b[..], epsilon),
+            (Curve3(a), Curve3(b)) => curves_approx_eq(&a[..], &b[..], epsilon),
+            (Curve4(a), Curve4(b)) => curves_approx_eq(&a[..], &b[..], epsilon),
+            (BufferF32(a), BufferF32(b)) => floats_approx_eq(a, b, epsilon),
+            _ => self == other,
+        }
+    }
+}
+
+impl ParameterObject {
+    /// Compare two objects, using [`Parameter::approx_eq`] for parameters.
+    /// Like `==`, this ignores the order of parameters.
+    pub fn approx_eq(&self, other: &ParameterObject, epsilon: f32) -> bool {
+        self.len() == other.len()
+            && self.0.iter().all(|(name, param)| {
+                other
+                    .0
+                    .get(name)
+                    .map_or(false, |p| param.approx_eq(p, epsilon))
+            })
+    }
+}
+
+impl ParameterList {
+    /// Compare two lists, using [`Parameter::approx_eq`] for parameters.
+    /// Like `==`, this ignores the order of objects and lists.
+    pub fn approx_eq(&self, other: &ParameterList, epsilon: f32) -> bool {
+        self.objects.len() == other.objects.len()
+            && self.lists.len() == other.lists.len()
+            && self.objects.0.iter().all(|(name, obj)| {
+                other
+                    .objects
+                    .0
+                    .get(name)
+                    .map_or(false, |o| obj.approx_eq(o, epsilon))
+            })
+            && self.lists.0.iter().all(|(name, list)| {
+                other
+                    .lists
+                    .0
+                    .get(name)
+                    .map_or(false, |l| list.approx_eq(l, epsilon))
+            })
+    }
+}
+
+impl ParameterIO {
+    /// Compare two documents, treating floats as equal if they differ by at
+    /// most `epsilon`. This is useful for comparing documents which took
+    /// different routes through binary and text, which may lose the last bit
+    /// of a float. See [`Parameter::approx_eq`].
+    pub fn approx_eq(&self, other: &ParameterIO, epsilon: f32) -> bool {
+        self.version == other.version
+            && self.data_type == other.data_type
+            && self.param_root.approx_eq(&other.param_root, epsilon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approx_eq() {
+        let pio = |f: f32| {
+            ParameterIO::new().with_list(
+                "List",
+                ParameterList::new().with_object(
+                    "Obj",
+                    ParameterObject::new()
+                        .with_parameter("F32", Parameter::F32(f))
+                        .with_parameter(
+                            "Vec3",
+                            Parameter::Vec3(Vector3f {
+                                x: f,
+                                y: 0.0,
+                                z: -f,
+                            }),
+                        )
+                        .with_parameter("Buf", Parameter::BufferF32(vec![f, f]))
+                        .with_parameter("I32", Parameter::I32(1)),
+                ),
+            )
+        };
+        assert!(pio(1.0).approx_eq(&pio(1.0005), 0.001));
+        assert!(!pio(1.0).approx_eq(&pio(1.01), 0.001));
+        assert!(pio(f32::NAN).approx_eq(&pio(f32::NAN), 0.0));
+        assert!(pio(f32::INFINITY).approx_eq(&pio(f32::INFINITY), 0.0));
+        assert!(!pio(1.0).approx_eq(&ParameterIO::new(), 1.0));
+        assert!(!Parameter::I32(1).approx_eq(&Parameter::I32(2), 10.0));
+        assert!(!Parameter::F32(1.0).approx_eq(&Parameter::I32(1), 10.0));
+    }
+}
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use super::*;
+pub use crate::util::CacheStats;
+use crate::util::ContentCache;
+
+/// Cache of parsed parameter archives, keyed by a hash of their binary
+/// contents.
+///
+/// The cache only holds weak references, so a parameter IO is dropped as
+/// soon as the last [`Arc`] returned for it goes away. This suits tools which
+/// reload the same files repeatedly, e.g. when watching a directory, and
+/// want unchanged files to skip parsing.
+///
+/// Entries are matched by a 64-bit content hash plus the data length, so
+/// different data is only treated as identical in the event of a hash
+/// collision.
+///
+/// ```
+/// # use roead::aamp::*;
+/// let cache = ParameterIOCache::new();
+/// let data = std::fs::read("test/aamp/Lizalfos.bphysics")?;
+/// let first = cache.get_or_parse(&data)?;
+/// let second = cache.get_or_parse(&data)?;
+/// assert!(std::sync::Arc::ptr_eq(&first, &second));
+/// assert_eq!(cache.stats(), CacheStats {
+///     hits:   1,
+///     misses: 1,
+/// });
+/// # Ok::<(), roead::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct ParameterIOCache(ContentCache<ParameterIO>);
+
+impl ParameterIOCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get the parameter IO for some binary data, parsing it only if the
+    /// same data is not already in the cache.
+    pub fn get_or_parse(&self, data: &[u8]) -> Result<Arc<ParameterIO>> {
+        self.0
+            .get_or_parse(data, |data| ParameterIO::from_binary(data))
+    }
+
+    /// Hit and miss counts since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        self.0.stats()
+    }
+
+    /// The number of cached parameter IOs which are still alive.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no cached parameter IOs are still alive.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all entries from the cache. The statistics are kept.
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache() {
+        let cache = ParameterIOCache::new();
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let first = cache.get_or_parse(&data).unwrap();
+        let second = cache.get_or_parse(&data).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(cache.len(), 1);
+
+        let other = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+        let third = cache.get_or_parse(&other).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+        assert_eq!(cache.len(), 2);
+
+        drop(first);
+        drop(second);
+        assert_eq!(cache.len(), 1);
+        let reparsed = cache.get_or_parse(&data).unwrap();
+        assert_eq!(*reparsed, ParameterIO::from_binary(&data).unwrap());
+
+        assert!(cache.get_or_parse(b"AAMP").is_err());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats(), CacheStats {
+            hits:   1,
+            misses: 4,
+        });
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn prune_threshold() {
+        let cache = ParameterIOCache::new();
+        let data = |i: u32| ParameterIO::new().with_version(i).to_binary();
+        for i in 0..200 {
+            // Each document is dropped right away, leaving a dead entry.
+            cache.get_or_parse(&data(i)).unwrap();
+        }
+        assert!(cache.is_empty());
+        // Dead entries are pruned in batches rather than on every miss.
+        assert!(cache.0.raw_len() <= crate::util::MIN_PRUNE_THRESHOLD);
+        assert!(cache.0.raw_len() > 1);
+    }
+}
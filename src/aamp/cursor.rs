@@ -0,0 +1,152 @@
+use super::*;
+
+/// Cursor for navigating a [`ParameterIO`] one level at a time, for code
+/// which does not know the structure of a document upfront.
+///
+/// The cursor starts at the root list. It can descend into child lists, or
+/// into a child object, whose parameters can then be read. Objects have no
+/// children, so from an object the cursor can only [`exit`](Self::exit).
+///
+/// ```
+/// # use roead::aamp::*;
+/// let pio = ParameterIO::from_binary(std::fs::read("test/aamp/GameRomHorse.bxml")?)?;
+/// let mut cursor = ParameterCursor::new(&pio);
+/// cursor.enter_object("LinkTarget")?;
+/// assert!(cursor.param("ModelUser").is_some());
+/// cursor.exit()?;
+/// assert!(cursor.current_path().is_empty());
+/// # Ok::<(), roead::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParameterCursor<'a> {
+    lists:  Vec<&'a ParameterList>,
+    object: Option<&'a ParameterObject>,
+    path:   Vec<Name>,
+}
+
+impl<'a> ParameterCursor<'a> {
+    /// Create a cursor positioned at the root list of a parameter IO.
+    pub fn new(pio: &'a ParameterIO) -> Self {
+        Self {
+            lists:  vec![&pio.param_root],
+            object: None,
+            path:   vec![],
+        }
+    }
+
+    fn path_string(&self) -> std::string::String {
+        let mut path = std::string::String::from("param_root");
+        for name in &self.path {
+            path.push('/');
+            path.push_str(&name.to_string());
+        }
+        path
+    }
+
+    /// Descend into a child list of the current list.
+    pub fn enter_list(&mut self, name: impl Into<Name>) -> Result<()> {
+        let name = name.into();
+        if self.object.is_some() {
+            return Err(Error::InvalidDataD(format!(
+                "Cannot enter list {name}: {} is a parameter object",
+                self.path_string()
+            )));
+        }
+        let list = self.current_list().list(name).ok_or_else(|| {
+            Error::InvalidDataD(format!("No list {name} in {}", self.path_string()))
+        })?;
+        self.lists.push(list);
+        self.path.push(name);
+        Ok(())
+    }
+
+    /// Descend into a child object of the current list.
+    pub fn enter_object(&mut self, name: impl Into<Name>) -> Result<()> {
+        let name = name.into();
+        if self.object.is_some() {
+            return Err(Error::InvalidDataD(format!(
+                "Cannot enter object {name}: {} is a parameter object",
+                self.path_string()
+            )));
+        }
+        let object = self.current_list().object(name).ok_or_else(|| {
+            Error::InvalidDataD(format!("No object {name} in {}", self.path_string()))
+        })?;
+        self.object = Some(object);
+        self.path.push(name);
+        Ok(())
+    }
+
+    /// Go up one level. Fails if the cursor is at the root list.
+    pub fn exit(&mut self) -> Result<()> {
+        if self.path.is_empty() {
+            return Err(Error::InvalidData("Cannot exit the root parameter list"));
+        }
+        self.path.pop();
+        if self.object.take().is_none() {
+            self.lists.pop();
+        }
+        Ok(())
+    }
+
+    /// Get a parameter of the current object. Returns `None` if the cursor is
+    /// not in an object or the object has no such parameter.
+    pub fn param(&self, name: impl Into<Name>) -> Option<&'a Parameter> {
+        self.object.and_then(|obj| obj.get(name))
+    }
+
+    /// The innermost list the cursor is in. If the cursor is in an object,
+    /// this is the list containing it.
+    pub fn current_list(&self) -> &'a ParameterList {
+        self.lists[self.lists.len() - 1]
+    }
+
+    /// The object the cursor is in, if any.
+    pub fn current_object(&self) -> Option<&'a ParameterObject> {
+        self.object
+    }
+
+    /// Names of the lists (and object) leading from the root list to the
+    /// current position.
+    pub fn current_path(&self) -> &[Name] {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn navigate() {
+        let pio = ParameterIO::new()
+            .with_object(
+                "Root",
+                ParameterObject::new().with_parameter("A", Parameter::I32(1)),
+            )
+            .with_list(
+                "Child",
+                ParameterList::new().with_object(
+                    "Obj",
+                    ParameterObject::new().with_parameter("B", Parameter::Bool(true)),
+                ),
+            );
+        let mut cursor = ParameterCursor::new(&pio);
+        assert!(cursor.param("A").is_none());
+        assert!(cursor.exit().is_err());
+        assert!(cursor.enter_list("Missing").is_err());
+
+        cursor.enter_list("Child").unwrap();
+        cursor.enter_object("Obj").unwrap();
+        assert_eq!(cursor.current_path(), ["Child".into(), "Obj".into()]);
+        assert_eq!(cursor.param("B"), Some(&Parameter::Bool(true)));
+        assert!(cursor.enter_object("Obj").is_err());
+        assert_eq!(cursor.current_list(), pio.list("Child").unwrap());
+
+        cursor.exit().unwrap();
+        assert!(cursor.current_object().is_none());
+        cursor.exit().unwrap();
+        cursor.enter_object("Root").unwrap();
+        assert_eq!(cursor.param("A"), Some(&Parameter::I32(1)));
+    }
+}
@@ -0,0 +1,153 @@
+//! Line diffs of the YAML representation of parameter IOs.
+use std::fmt::Write;
+
+use super::*;
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Diff two texts line by line.
+fn diff_lines<'a>(a: &'a str, b: &'a str) -> Vec<(Op, &'a str)> {
+    let a: Vec<_> = a.lines().collect();
+    let b: Vec<_> = b.lines().collect();
+    let mut ops = vec![];
+    let (mut x, mut y) = (0, 0);
+    for (i, j) in crate::util::common_subsequence(&a, &b)
+        .into_iter()
+        .chain(std::iter::once((a.len(), b.len())))
+    {
+        ops.extend(a[x..i].iter().map(|line| (Op::Delete, *line)));
+        ops.extend(b[y..j].iter().map(|line| (Op::Insert, *line)));
+        if i < a.len() {
+            ops.push((Op::Equal, a[i]));
+        }
+        x = i + 1;
+        y = j + 1;
+    }
+    ops
+}
+
+/// Format an edit script as unified diff hunks.
+fn unified(ops: &[(Op, &str)]) -> std::string::String {
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Equal)
+        .map(|(i, _)| i)
+        .collect();
+    let mut out = std::string::String::new();
+    if changes.is_empty() {
+        return out;
+    }
+    out.push_str("--- base\n+++ modified\n");
+    let mut i = 0;
+    while i < changes.len() {
+        // Changes separated by no more than twice the context share a hunk.
+        let mut j = i;
+        while j + 1 < changes.len() && changes[j + 1] - changes[j] <= 2 * CONTEXT + 1 {
+            j += 1;
+        }
+        let start = changes[i].saturating_sub(CONTEXT);
+        let end = (changes[j] + CONTEXT + 1).min(ops.len());
+        let count =
+            |range: &[(Op, &str)], skip: Op| range.iter().filter(|(op, _)| *op != skip).count();
+        let (old_before, new_before) = (
+            count(&ops[..start], Op::Insert),
+            count(&ops[..start], Op::Delete),
+        );
+        let (old_len, new_len) = (
+            count(&ops[start..end], Op::Insert),
+            count(&ops[start..end], Op::Delete),
+        );
+        let line = |before: usize, len: usize| if len == 0 { before } else { before + 1 };
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            line(old_before, old_len),
+            old_len,
+            line(new_before, new_len),
+            new_len
+        );
+        for (op, text) in &ops[start..end] {
+            let prefix = match op {
+                Op::Equal => ' ',
+                Op::Delete => '-',
+                Op::Insert => '+',
+            };
+            let _ = writeln!(out, "{prefix}{text}");
+        }
+        i = j + 1;
+    }
+    out
+}
+
+impl ParameterIO {
+    /// Produce a unified diff of the YAML representations of two parameter
+    /// IOs, with `---`/`+++` headers and three lines of context around each
+    /// change. Returns an empty string if the texts are identical.
+    ///
+    /// This is only available with the `yaml` feature.
+    ///
+    /// ```
+    /// # use roead::aamp::*;
+    /// let base = ParameterIO::new().with_object(
+    ///     "Obj",
+    ///     ParameterObject::new().with_parameter("A", Parameter::I32(1)),
+    /// );
+    /// let mut modified = base.clone();
+    /// modified
+    ///     .object_mut("Obj")
+    ///     .unwrap()
+    ///     .insert("A", Parameter::I32(2));
+    /// let diff = ParameterIO::diff_text(&base, &modified);
+    /// let a = Name::from("A");
+    /// assert!(diff.contains(&format!("-      {a}: 1\n+      {a}: 2\n")));
+    /// assert!(ParameterIO::diff_text(&base, &base).is_empty());
+    /// ```
+    pub fn diff_text(base: &ParameterIO, modified: &ParameterIO) -> std::string::String {
+        let (base, modified) = (base.to_text(), modified.to_text());
+        unified(&diff_lines(&base, &modified))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unified_diff() {
+        let a = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12\n13\n14\n15\n";
+        let b = "1\n2\nthree\n4\n5\n6\n7\n8\n9\n10\n11\n12\n14\n15\n16\n";
+        assert_eq!(
+            unified(&diff_lines(a, b)),
+            "--- base\n+++ modified\n@@ -1,6 +1,6 @@\n 1\n 2\n-3\n+three\n 4\n 5\n 6\n@@ -10,6 \
+             +10,6 @@\n 10\n 11\n 12\n-13\n 14\n 15\n+16\n"
+        );
+        assert_eq!(
+            unified(&diff_lines("", "a\n")),
+            "--- base\n+++ modified\n@@ -0,0 +1,1 @@\n+a\n"
+        );
+        assert!(unified(&diff_lines(a, a)).is_empty());
+    }
+
+    #[test]
+    fn large_diff() {
+        // Every line differs, which is far beyond the edit distance searched
+        // for, so the whole text is reported as replaced.
+        let a: std::string::String = (0..20000).map(|i| format!("a{i}\n")).collect();
+        let b: std::string::String = (0..20000).map(|i| format!("b{i}\n")).collect();
+        let ops = diff_lines(&a, &b);
+        assert_eq!(ops.len(), 40000);
+        assert!(ops[..20000].iter().all(|(op, _)| *op == Op::Delete));
+        assert!(ops[20000..].iter().all(|(op, _)| *op == Op::Insert));
+        let diff = unified(&ops);
+        assert!(diff.starts_with("--- base\n+++ modified\n@@ -1,20000 +1,20000 @@\n-a0\n"));
+    }
+}
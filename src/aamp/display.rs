@@ -0,0 +1,126 @@
+//! Human-readable [`Display`] output for parameter structures. This is meant
+//! for debugging and has no parsing counterpart; use the YAML or JSON formats
+//! for anything which needs to be read back.
+use std::fmt::{Display, Formatter, Result};
+
+use super::*;
+
+/// Same rule as the BYML text emitter: small objects without nested
+/// structures (here, buffers and curves) are written on a single line.
+fn should_use_inline(obj: &ParameterObject) -> bool {
+    let is_simple = |param: &Parameter| {
+        !matches!(
+            param,
+            Parameter::BufferInt(_)
+                | Parameter::BufferF32(_)
+                | Parameter::BufferU32(_)
+                | Parameter::BufferBinary(_)
+                | Parameter::Curve1(_)
+                | Parameter::Curve2(_)
+                | Parameter::Curve3(_)
+                | Parameter::Curve4(_)
+        )
+    };
+    obj.len() < 10 && obj.0.values().all(is_simple)
+}
+
+fn write_object(f: &mut Formatter<'_>, obj: &ParameterObject, indent: usize) -> Result {
+    if obj.is_empty() {
+        return f.write_str("{}");
+    }
+    if should_use_inline(obj) {
+        f.write_str("{ ")?;
+        for (i, (name, param)) in obj.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{name}: {param:?}")?;
+        }
+        return f.write_str(" }");
+    }
+    f.write_str("{\n")?;
+    for (name, param) in obj.iter() {
+        writeln!(f, "{:width$}{name}: {param:?}", "", width = indent + 2)?;
+    }
+    write!(f, "{:indent$}}}", "")
+}
+
+/// Objects are listed before child lists.
+fn write_list(f: &mut Formatter<'_>, list: &ParameterList, indent: usize) -> Result {
+    if list.objects.is_empty() && list.lists.is_empty() {
+        return f.write_str("{}");
+    }
+    f.write_str("{\n")?;
+    for (name, obj) in list.objects.iter() {
+        write!(f, "{:width$}{name}: ", "", width = indent + 2)?;
+        write_object(f, obj, indent + 2)?;
+        f.write_str("\n")?;
+    }
+    for (name, list) in list.lists.iter() {
+        write!(f, "{:width$}{name}: ", "", width = indent + 2)?;
+        write_list(f, list, indent + 2)?;
+        f.write_str("\n")?;
+    }
+    write!(f, "{:indent$}}}", "")
+}
+
+impl Display for ParameterObject {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write_object(f, self, 0)
+    }
+}
+
+impl Display for ParameterList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write_list(f, self, 0)
+    }
+}
+
+impl Display for ParameterIO {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "ParameterIO (version {}, type {}) param_root: ",
+            self.version, self.data_type
+        )?;
+        write_list(f, &self.param_root, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let small = ParameterObject::new()
+            .with_parameter("A", Parameter::Bool(true))
+            .with_parameter("B", Parameter::F32(1.5));
+        let large = ParameterObject::new().with_parameter("Buf", Parameter::BufferU32(vec![1]));
+        let pio = ParameterIO::new()
+            .with_object("Small", small.clone())
+            .with_list(
+                "Child",
+                ParameterList::new()
+                    .with_object("Large", large)
+                    .with_list("Empty", ParameterList::new()),
+            );
+        let a = Name::from("A");
+        let b = Name::from("B");
+        assert_eq!(
+            small.to_string(),
+            format!("{{ {a}: Bool(true), {b}: F32(1.5) }}")
+        );
+        let expected = format!(
+            "ParameterIO (version 0, type xml) param_root: {{\n  {small_name}: {{ {a}: \
+             Bool(true), {b}: F32(1.5) }}\n  {child}: {{\n    {large}: {{\n      {buf}: \
+             BufferU32([1])\n    }}\n    {empty}: {{}}\n  }}\n}}",
+            small_name = Name::from("Small"),
+            child = Name::from("Child"),
+            large = Name::from("Large"),
+            buf = Name::from("Buf"),
+            empty = Name::from("Empty"),
+        );
+        assert_eq!(pio.to_string(), expected);
+    }
+}
@@ -0,0 +1,620 @@
+use serde_json::{json, Map, Value};
+
+use super::*;
+
+fn name_to_key(name: &Name) -> std::string::String {
+    format!("{:#010x}", name.0)
+}
+
+fn key_to_name(key: &str) -> Result<Name> {
+    match key.strip_prefix("0x") {
+        Some(hex) => {
+            u32::from_str_radix(hex, 16)
+                .map(Name)
+                .map_err(|_| Error::InvalidDataD(format!("Invalid name hash: {key}")))
+        }
+        None => Ok(Name::from(key)),
+    }
+}
+
+/// Non-finite floats have no JSON representation, so they are stored as
+/// strings instead.
+fn float_to_json(value: f32) -> Value {
+    if value.is_nan() {
+        "nan".into()
+    } else if value.is_infinite() {
+        if value.is_sign_positive() {
+            "inf".into()
+        } else {
+            "-inf".into()
+        }
+    } else {
+        // Go through the shortest representation so that the JSON does not end
+        // up with f64 noise like 0.10000000149011612.
+        value
+            .to_string()
+            .parse::<f64>()
+            .map(Value::from)
+            .unwrap_or(Value::Null)
+    }
+}
+
+fn float_from_json(value: &Value) -> Result<f32> {
+    match value {
+        Value::Number(num) => {
+            num.as_f64()
+                .map(|v| v as f32)
+                .ok_or(Error::InvalidData("Invalid float in JSON parameter"))
+        }
+        Value::String(s) => {
+            match s.as_str() {
+                "nan" => Ok(f32::NAN),
+                "inf" => Ok(f32::INFINITY),
+                "-inf" => Ok(f32::NEG_INFINITY),
+                _ => Err(Error::InvalidDataD(format!("Invalid float: {s}"))),
+            }
+        }
+        _ => Err(Error::InvalidData("Invalid float in JSON parameter")),
+    }
+}
+
+fn field<'a>(obj: &'a Map<std::string::String, Value>, key: &'static str) -> Result<&'a Value> {
+    obj.get(key)
+        .ok_or_else(|| Error::InvalidDataD(format!("Missing field `{key}` in JSON parameter")))
+}
+
+fn float_field(obj: &Map<std::string::String, Value>, key: &'static str) -> Result<f32> {
+    float_from_json(field(obj, key)?)
+}
+
+fn array_field<'a>(
+    obj: &'a Map<std::string::String, Value>,
+    key: &'static str,
+) -> Result<&'a Vec<Value>> {
+    field(obj, key)?
+        .as_array()
+        .ok_or(Error::InvalidData("Expected array in JSON parameter"))
+}
+
+fn int_from_json<T: TryFrom<i64>>(value: &Value) -> Result<T> {
+    value
+        .as_i64()
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or(Error::InvalidData("Invalid integer in JSON parameter"))
+}
+
+fn curves_to_json(curves: &[Curve]) -> Value {
+    curves
+        .iter()
+        .map(|curve| {
+            json!({
+                "a": curve.a,
+                "b": curve.b,
+                "floats": curve.floats.iter().copied().map(float_to_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect()
+}
+
+fn curves_from_json<const N: usize>(obj: &Map<std::string::String, Value>) -> Result<[Curve; N]> {
+    let values = array_field(obj, "curves")?;
+    if values.len() != N {
+        return Err(Error::InvalidDataD(format!(
+            "Expected {N} curves in JSON parameter, found {}",
+            values.len()
+        )));
+    }
+    let mut curves = [Curve::default(); N];
+    for (curve, value) in curves.iter_mut().zip(values) {
+        let value = value
+            .as_object()
+            .ok_or(Error::InvalidData("Expected object for curve"))?;
+        curve.a = int_from_json(field(value, "a")?)?;
+        curve.b = int_from_json(field(value, "b")?)?;
+        let floats = array_field(value, "floats")?;
+        if floats.len() != curve.floats.len() {
+            return Err(Error::InvalidData("Curves must have exactly 30 floats"));
+        }
+        for (f, value) in curve.floats.iter_mut().zip(floats) {
+            *f = float_from_json(value)?;
+        }
+    }
+    Ok(curves)
+}
+
+fn parameter_to_json(param: &Parameter) -> Value {
+    let tagged = |value: Value| json!({ "type": param.type_name().as_str(), "value": value });
+    match param {
+        Parameter::Bool(b) => Value::Bool(*b),
+        Parameter::F32(f) => {
+            if f.is_finite() {
+                float_to_json(*f)
+            } else {
+                tagged(float_to_json(*f))
+            }
+        }
+        Parameter::I32(i) => Value::from(*i),
+        Parameter::StringRef(s) => Value::from(s.as_str()),
+        Parameter::Vec2(v) => {
+            json!({ "type": "Vec2", "x": float_to_json(v.x), "y": float_to_json(v.y) })
+        }
+        Parameter::Vec3(v) => {
+            json!({
+                "type": "Vec3",
+                "x": float_to_json(v.x),
+                "y": float_to_json(v.y),
+                "z": float_to_json(v.z),
+            })
+        }
+        Parameter::Vec4(v) => {
+            json!({
+                "type": "Vec4",
+                "x": float_to_json(v.x),
+                "y": float_to_json(v.y),
+                "z": float_to_json(v.z),
+                "t": float_to_json(v.t),
+            })
+        }
+        Parameter::Color(c) => {
+            json!({
+                "type": "Color",
+                "r": float_to_json(c.r),
+                "g": float_to_json(c.g),
+                "b": float_to_json(c.b),
+                "a": float_to_json(c.a),
+            })
+        }
+        Parameter::Quat(q) => {
+            json!({
+                "type": "Quat",
+                "a": float_to_json(q.a),
+                "b": float_to_json(q.b),
+                "c": float_to_json(q.c),
+                "d": float_to_json(q.d),
+            })
+        }
+        Parameter::String32(s) => tagged(s.as_str().into()),
+        Parameter::String64(s) => tagged(s.as_str().into()),
+        Parameter::String256(s) => tagged(s.as_str().into()),
+        Parameter::U32(u) => tagged((*u).into()),
+        Parameter::Curve1(c) => json!({ "type": "Curve1", "curves": curves_to_json(&c[..]) }),
+        Parameter::Curve2(c) => json!({ "type": "Curve2", "curves": curves_to_json(&c[..]) }),
+        Parameter::Curve3(c) => json!({ "type": "Curve3", "curves": curves_to_json(&c[..]) }),
+        Parameter::Curve4(c) => json!({ "type": "Curve4", "curves": curves_to_json(&c[..]) }),
+        Parameter::BufferInt(buf) => tagged(buf.as_slice().into()),
+        Parameter::BufferF32(buf) => tagged(buf.iter().copied().map(float_to_json).collect()),
+        Parameter::BufferU32(buf) => tagged(buf.as_slice().into()),
+        Parameter::BufferBinary(buf) => tagged(buf.as_slice().into()),
+    }
+}
+
+/// Parse a parameter in either encoding: plain values and objects tagged with
+/// a [`Parameter::type_name`] as written by [`parameter_to_json`], or
+/// `{"type": ..., "value": ...}` with a snake case type name as written by
+/// [`parameter_to_uniform_json`].
+fn parameter_from_json(value: &Value) -> Result<Parameter> {
+    if let Some(Value::String(type_)) = value.get("type")
+        && type_.starts_with(|c: char| c.is_ascii_lowercase())
+    {
+        return parameter_from_uniform_json(value);
+    }
+    let obj = match value {
+        Value::Bool(b) => return Ok(Parameter::Bool(*b)),
+        Value::Number(num) => {
+            return match num.as_i64() {
+                Some(i) => {
+                    i32::try_from(i)
+                        .map(Parameter::I32)
+                        .map_err(|_| Error::InvalidDataD(format!("Integer out of range: {i}")))
+                }
+                None => float_from_json(value).map(Parameter::F32),
+            };
+        }
+        Value::String(s) => return Ok(Parameter::StringRef(s.as_str().into())),
+        Value::Object(obj) => obj,
+        _ => return Err(Error::InvalidData("Invalid JSON parameter value")),
+    };
+    let type_ = field(obj, "type")?
+        .as_str()
+        .ok_or(Error::InvalidData("Parameter type must be a string"))?;
+    let str_value = || -> Result<&str> {
+        field(obj, "value")?
+            .as_str()
+            .ok_or(Error::InvalidData("Expected string in JSON parameter"))
+    };
+    let buffer = || -> Result<&Vec<Value>> { array_field(obj, "value") };
+    Ok(match type_ {
+        "Bool" => {
+            Parameter::Bool(
+                field(obj, "value")?
+                    .as_bool()
+                    .ok_or(Error::InvalidData("Expected bool in JSON parameter"))?,
+            )
+        }
+        "I32" => Parameter::I32(int_from_json(field(obj, "value")?)?),
+        "StringRef" => Parameter::StringRef(str_value()?.into()),
+        "F32" => Parameter::F32(float_field(obj, "value")?),
+        "Vec2" => {
+            Parameter::Vec2(Vector2f {
+                x: float_field(obj, "x")?,
+                y: float_field(obj, "y")?,
+            })
+        }
+        "Vec3" => {
+            Parameter::Vec3(Vector3f {
+                x: float_field(obj, "x")?,
+                y: float_field(obj, "y")?,
+                z: float_field(obj, "z")?,
+            })
+        }
+        "Vec4" => {
+            Parameter::Vec4(Vector4f {
+                x: float_field(obj, "x")?,
+                y: float_field(obj, "y")?,
+                z: float_field(obj, "z")?,
+                t: float_field(obj, "t")?,
+            })
+        }
+        "Color" => {
+            Parameter::Color(Color {
+                r: float_field(obj, "r")?,
+                g: float_field(obj, "g")?,
+                b: float_field(obj, "b")?,
+                a: float_field(obj, "a")?,
+            })
+        }
+        "Quat" => {
+            Parameter::Quat(Quat {
+                a: float_field(obj, "a")?,
+                b: float_field(obj, "b")?,
+                c: float_field(obj, "c")?,
+                d: float_field(obj, "d")?,
+            })
+        }
+        "String32" => Parameter::String32(str_value()?.into()),
+        "String64" => Parameter::String64(Box::new(str_value()?.into())),
+        "String256" => Parameter::String256(Box::new(str_value()?.into())),
+        "U32" => Parameter::U32(int_from_json(field(obj, "value")?)?),
+        "Curve1" => Parameter::Curve1(Box::new(curves_from_json(obj)?)),
+        "Curve2" => Parameter::Curve2(Box::new(curves_from_json(obj)?)),
+        "Curve3" => Parameter::Curve3(Box::new(curves_from_json(obj)?)),
+        "Curve4" => Parameter::Curve4(Box::new(curves_from_json(obj)?)),
+        "BufferInt" => {
+            Parameter::BufferInt(buffer()?.iter().map(int_from_json).collect::<Result<_>>()?)
+        }
+        "BufferF32" => {
+            Parameter::BufferF32(
+                buffer()?
+                    .iter()
+                    .map(float_from_json)
+                    .collect::<Result<_>>()?,
+            )
+        }
+        "BufferU32" => {
+            Parameter::BufferU32(buffer()?.iter().map(int_from_json).collect::<Result<_>>()?)
+        }
+        "BufferBinary" => {
+            Parameter::BufferBinary(buffer()?.iter().map(int_from_json).collect::<Result<_>>()?)
+        }
+        _ => {
+            return Err(Error::InvalidDataD(format!(
+                "Unknown parameter type: {type_}"
+            )));
+        }
+    })
+}
+
+/// Type names in the uniform encoding are the snake case forms of the
+/// [`Parameter::type_name`]s, e.g. `f32`, `vec3` or `buffer_int`.
+fn uniform_type_name(type_name: &str) -> std::string::String {
+    let mut name = std::string::String::with_capacity(type_name.len() + 2);
+    for (i, c) in type_name.chars().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            name.push('_');
+        }
+        name.push(c.to_ascii_lowercase());
+    }
+    name
+}
+
+fn type_name_from_uniform(name: &str) -> std::string::String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Encode a parameter as `{"type": ..., "value": ...}`, whatever its type.
+/// Vectors, colors and quaternions have an object of their components as the
+/// value, and curves an array of curves.
+fn parameter_to_uniform_json(param: &Parameter) -> Value {
+    let value = match parameter_to_json(param) {
+        Value::Object(mut obj) => {
+            obj.remove("type");
+            if let Some(value) = obj.remove("value").or_else(|| obj.remove("curves")) {
+                value
+            } else {
+                Value::Object(obj)
+            }
+        }
+        value => value,
+    };
+    json!({ "type": uniform_type_name(param.type_str()), "value": value })
+}
+
+fn parameter_from_uniform_json(value: &Value) -> Result<Parameter> {
+    let obj = value
+        .as_object()
+        .ok_or(Error::InvalidData("Parameter must be a JSON object"))?;
+    let type_ = type_name_from_uniform(
+        field(obj, "type")?
+            .as_str()
+            .ok_or(Error::InvalidData("Parameter type must be a string"))?,
+    );
+    let value = field(obj, "value")?;
+    let tagged = match value {
+        Value::Object(components) => {
+            let mut components = components.clone();
+            components.insert("type".into(), type_.into());
+            Value::Object(components)
+        }
+        _ if type_.starts_with("Curve") => json!({ "type": type_, "curves": value }),
+        _ => json!({ "type": type_, "value": value }),
+    };
+    parameter_from_json(&tagged)
+}
+
+type ToJson = fn(&Parameter) -> Value;
+
+fn object_to_json(obj: &ParameterObject, to_json: ToJson) -> Value {
+    Value::Object(
+        obj.iter()
+            .map(|(name, param)| (name_to_key(name), to_json(param)))
+            .collect(),
+    )
+}
+
+fn object_from_json(value: &Value) -> Result<ParameterObject> {
+    value
+        .as_object()
+        .ok_or(Error::InvalidData("Parameter object must be a JSON object"))?
+        .iter()
+        .map(|(key, value)| Ok((key_to_name(key)?, parameter_from_json(value)?)))
+        .collect::<Result<_>>()
+        .map(ParameterObject)
+}
+
+fn list_to_json(list: &ParameterList, to_json: ToJson) -> Value {
+    json!({
+        "objects": Value::Object(
+            list.objects
+                .iter()
+                .map(|(name, obj)| (name_to_key(name), object_to_json(obj, to_json)))
+                .collect()
+        ),
+        "lists": Value::Object(
+            list.lists
+                .iter()
+                .map(|(name, list)| (name_to_key(name), list_to_json(list, to_json)))
+                .collect()
+        ),
+    })
+}
+
+fn list_from_json(value: &Value) -> Result<ParameterList> {
+    let value = value
+        .as_object()
+        .ok_or(Error::InvalidData("Parameter list must be a JSON object"))?;
+    let entries = |key: &'static str| -> Result<&Map<std::string::String, Value>> {
+        match value.get(key) {
+            Some(Value::Object(map)) => Ok(map),
+            Some(_) => {
+                Err(Error::InvalidDataD(format!(
+                    "`{key}` must be a JSON object"
+                )))
+            }
+            None => {
+                Err(Error::InvalidDataD(format!(
+                    "Missing `{key}` in parameter list"
+                )))
+            }
+        }
+    };
+    Ok(ParameterList {
+        objects: entries("objects")?
+            .iter()
+            .map(|(key, value)| Ok((key_to_name(key)?, object_from_json(value)?)))
+            .collect::<Result<_>>()
+            .map(ParameterObjectMap)?,
+        lists:   entries("lists")?
+            .iter()
+            .map(|(key, value)| Ok((key_to_name(key)?, list_from_json(value)?)))
+            .collect::<Result<_>>()
+            .map(ParameterListMap)?,
+    })
+}
+
+fn pio_to_json(pio: &ParameterIO, to_json: ToJson) -> Value {
+    json!({
+        "version": pio.version,
+        "data_type": pio.data_type.as_str(),
+        "param_root": list_to_json(&pio.param_root, to_json),
+    })
+}
+
+fn pio_from_json(value: &Value) -> Result<ParameterIO> {
+    let version = value
+        .get("version")
+        .map(int_from_json)
+        .transpose()?
+        .unwrap_or_default();
+    let data_type = match value.get("data_type") {
+        Some(Value::String(data_type)) => data_type.as_str().into(),
+        Some(_) => return Err(Error::InvalidData("Data type must be a string")),
+        None => "xml".into(),
+    };
+    let param_root = list_from_json(
+        value
+            .get("param_root")
+            .ok_or(Error::InvalidData("Missing `param_root` in parameter IO"))?,
+    )?;
+    Ok(ParameterIO {
+        version,
+        data_type,
+        param_root,
+    })
+}
+
+impl ParameterIO {
+    /// Convert the parameter IO to a JSON value.
+    ///
+    /// Names are written as hex strings (e.g. `"0xa4f6cb6c"`). Parameters with
+    /// a natural JSON equivalent (bools, floats, ints, and string refs) are
+    /// written as plain JSON values; every other type is written as an object
+    /// tagged with its type, e.g. `{"type": "Vec3", "x": 1.0, "y": 2.0, "z":
+    /// 3.0}` or `{"type": "U32", "value": 1}`. For a schema in which every
+    /// parameter is tagged, use the `From<&ParameterIO>` conversion to
+    /// [`Value`] instead.
+    ///
+    /// This is only available with the `with-serde` feature.
+    pub fn to_json_value(&self) -> Value {
+        pio_to_json(self, parameter_to_json)
+    }
+
+    /// Parse a parameter IO from a JSON value in the format produced by
+    /// [`ParameterIO::to_json_value`] or by the `From<&ParameterIO>`
+    /// conversion to [`Value`]. Both parameter encodings may be mixed in one
+    /// document. Keys which are not hex strings are treated as names and
+    /// hashed.
+    ///
+    /// This is only available with the `with-serde` feature.
+    pub fn from_json_value(value: &Value) -> Result<ParameterIO> {
+        pio_from_json(value)
+    }
+}
+
+impl From<&ParameterIO> for Value {
+    /// Convert the parameter IO to a JSON value in which every parameter,
+    /// whatever its type, is written as `{"type": "f32", "value": 1.5}`. Type
+    /// names are snake case (`bool`, `vec3`, `string_ref`, `buffer_f32`, ...).
+    /// Vectors, colors and quaternions have an object of their components as
+    /// the value, e.g. `{"x": 1.0, "y": 2.0}`, and curves an array of curves.
+    /// Names and the overall layout are the same as for
+    /// [`ParameterIO::to_json_value`].
+    ///
+    /// This is only available with the `with-serde` feature.
+    fn from(pio: &ParameterIO) -> Self {
+        pio_to_json(pio, parameter_to_uniform_json)
+    }
+}
+
+impl From<ParameterIO> for Value {
+    fn from(pio: ParameterIO) -> Self {
+        (&pio).into()
+    }
+}
+
+impl TryFrom<&Value> for ParameterIO {
+    type Error = Error;
+
+    /// Parse a parameter IO from JSON. This is the same as
+    /// [`ParameterIO::from_json_value`], so it accepts the output of both the
+    /// `From<&ParameterIO>` conversion and [`ParameterIO::to_json_value`].
+    fn try_from(value: &Value) -> Result<Self> {
+        pio_from_json(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_roundtrip() {
+        for file in jwalk::WalkDir::new("test/aamp")
+            .into_iter()
+            .filter_map(|f| {
+                f.ok().and_then(|f| {
+                    (f.file_type().is_file() && !f.file_name().to_str().unwrap().ends_with("yml"))
+                        .then(|| f.path())
+                })
+            })
+        {
+            let pio = ParameterIO::from_binary(std::fs::read(&file).unwrap()).unwrap();
+            let value = pio.to_json_value();
+            let text = serde_json::to_string(&value).unwrap();
+            let value: Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(pio, ParameterIO::from_json_value(&value).unwrap());
+            assert_eq!(pio, ParameterIO::try_from(&Value::from(&pio)).unwrap());
+            // Either reader accepts either writer's output.
+            assert_eq!(pio, ParameterIO::try_from(&pio.to_json_value()).unwrap());
+            assert_eq!(
+                pio,
+                ParameterIO::from_json_value(&Value::from(&pio)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn json_schema() {
+        let pio = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter(
+                    "Vec",
+                    Parameter::Vec3(Vector3f {
+                        x: 1.0,
+                        y: 2.0,
+                        z: 3.0,
+                    }),
+                )
+                .with_parameter("Float", Parameter::F32(0.1)),
+        );
+        let value = pio.to_json_value();
+        let obj = &value["param_root"]["objects"][format!("{:#010x}", hash_name("Obj"))];
+        assert_eq!(
+            obj[format!("{:#010x}", hash_name("Vec"))],
+            json!({"type": "Vec3", "x": 1.0, "y": 2.0, "z": 3.0})
+        );
+        assert_eq!(obj[format!("{:#010x}", hash_name("Float"))], json!(0.1));
+    }
+
+    #[test]
+    fn json_conversions() {
+        let pio = ParameterIO::from_binary(std::fs::read("test/aamp/GameRomHorse.bxml").unwrap())
+            .unwrap();
+        let value = Value::from(&pio);
+        assert_eq!(value["data_type"], "xml");
+        assert_eq!(ParameterIO::try_from(&value).unwrap(), pio);
+        assert_eq!(Value::from(pio), value);
+        assert!(ParameterIO::try_from(&json!({ "version": 0 })).is_err());
+
+        let pio = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter("Float", Parameter::F32(1.5))
+                .with_parameter("Ref", Parameter::StringRef("A".into()))
+                .with_parameter("Vec", Parameter::Vec2(Vector2f { x: 1.0, y: 2.0 }))
+                .with_parameter("Buf", Parameter::BufferInt(vec![1, 2])),
+        );
+        let value = Value::from(&pio);
+        let obj = &value["param_root"]["objects"][format!("{:#010x}", hash_name("Obj"))];
+        let param = |name| &obj[format!("{:#010x}", hash_name(name))];
+        assert_eq!(param("Float"), &json!({"type": "f32", "value": 1.5}));
+        assert_eq!(param("Ref"), &json!({"type": "string_ref", "value": "A"}));
+        assert_eq!(
+            param("Vec"),
+            &json!({"type": "vec2", "value": {"x": 1.0, "y": 2.0}})
+        );
+        assert_eq!(
+            param("Buf"),
+            &json!({"type": "buffer_int", "value": [1, 2]})
+        );
+        assert_eq!(ParameterIO::try_from(&value).unwrap(), pio);
+    }
+}
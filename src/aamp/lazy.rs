@@ -0,0 +1,258 @@
+use std::{io::Cursor, sync::Arc};
+
+use once_cell::sync::OnceCell;
+
+use super::{
+    parser::{Parser, MAX_LIST_DEPTH},
+    *,
+};
+
+fn parser(data: &[u8]) -> Result<Parser<Cursor<&[u8]>>> {
+    Parser::new(Cursor::new(data), Default::default())
+}
+
+/// Read-only view of a binary parameter archive which parses lists and
+/// objects only when they are first accessed. Parsed parts are cached, so
+/// each is parsed at most once.
+///
+/// This is meant for scanning many archives for a few specific parameters.
+/// To modify the document, convert it to a regular [`ParameterIO`] with
+/// [`LazyParameterIO::into_owned`].
+///
+/// Since parsing is deferred, errors in the archive may only be reported when
+/// the affected list or object is accessed.
+///
+/// ```
+/// # use roead::aamp::*;
+/// let data = std::fs::read("test/aamp/GameRomHorse.bxml")?;
+/// let pio = ParameterIO::from_binary_lazy(std::sync::Arc::new(data))?;
+/// let link_target = pio.object("LinkTarget")?.unwrap();
+/// assert!(link_target.get("ModelUser").is_some());
+/// # Ok::<(), roead::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct LazyParameterIO {
+    version: u32,
+    data_type: String,
+    param_root: LazyParameterList,
+}
+
+impl ParameterIO {
+    /// Load a parameter archive from binary data for lazy access. Only the
+    /// header and the root list header are parsed upfront.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    pub fn from_binary_lazy(data: Arc<Vec<u8>>) -> Result<LazyParameterIO> {
+        #[cfg(feature = "yaz0")]
+        let data = if data.starts_with(b"Yaz0") {
+            Arc::new(crate::yaz0::decompress(data.as_slice())?)
+        } else {
+            data
+        };
+        let mut parser = parser(&data)?;
+        let (version, data_type) = parser.pio_info()?;
+        let param_root = LazyParameterList::new(data.clone(), parser.root_offset()?, 0)?;
+        if param_root.name != ROOT_KEY {
+            return Err(Error::InvalidData(
+                "No param root found in parameter archive",
+            ));
+        }
+        Ok(LazyParameterIO {
+            version,
+            data_type,
+            param_root,
+        })
+    }
+}
+
+impl LazyParameterIO {
+    /// Data version (not the AAMP format version).
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Data type identifier.
+    pub fn data_type(&self) -> &str {
+        &self.data_type
+    }
+
+    /// The root parameter list.
+    pub fn param_root(&self) -> &LazyParameterList {
+        &self.param_root
+    }
+
+    /// Get a child list of the root list, parsing its header if needed.
+    pub fn list(&self, name: impl Into<Name>) -> Result<Option<&LazyParameterList>> {
+        self.param_root.list(name)
+    }
+
+    /// Get a child object of the root list, parsing it if needed.
+    pub fn object(&self, name: impl Into<Name>) -> Result<Option<&ParameterObject>> {
+        self.param_root.object(name)
+    }
+
+    /// Fully parse the archive into a regular [`ParameterIO`].
+    pub fn into_owned(self) -> Result<ParameterIO> {
+        Ok(ParameterIO {
+            version: self.version,
+            data_type: self.data_type,
+            param_root: self.param_root.to_owned()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+struct LazyParameterObject {
+    offset: u32,
+    parsed: OnceCell<ParameterObject>,
+}
+
+/// Lazily parsed [`ParameterList`]. See [`LazyParameterIO`].
+pub struct LazyParameterList {
+    data: Arc<Vec<u8>>,
+    name: Name,
+    offset: u32,
+    depth: usize,
+    list_offsets: Vec<u32>,
+    object_offsets: Vec<u32>,
+    lists: OnceCell<ParameterStructureMap<LazyParameterList>>,
+    objects: OnceCell<ParameterStructureMap<LazyParameterObject>>,
+}
+
+impl std::fmt::Debug for LazyParameterList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LazyParameterList")
+            .field("name", &self.name)
+            .field("offset", &self.offset)
+            .field("lists", &self.lists)
+            .field("objects", &self.objects)
+            .finish()
+    }
+}
+
+impl LazyParameterList {
+    fn new(data: Arc<Vec<u8>>, offset: u32, depth: usize) -> Result<Self> {
+        if depth > MAX_LIST_DEPTH {
+            return Err(Error::InvalidData("Parameter lists nested too deeply"));
+        }
+        let (name, list_offsets, object_offsets) = parser(&data)?.parse_list_header(offset)?;
+        Ok(Self {
+            data,
+            name,
+            offset,
+            depth,
+            list_offsets,
+            object_offsets,
+            lists: OnceCell::new(),
+            objects: OnceCell::new(),
+        })
+    }
+
+    fn lists_map(&self) -> Result<&ParameterStructureMap<LazyParameterList>> {
+        self.lists.get_or_try_init(|| {
+            self.list_offsets
+                .iter()
+                .map(|&offset| {
+                    let list = Self::new(self.data.clone(), offset, self.depth + 1)?;
+                    Ok((list.name, list))
+                })
+                .collect()
+        })
+    }
+
+    fn objects_map(&self) -> Result<&ParameterStructureMap<LazyParameterObject>> {
+        self.objects.get_or_try_init(|| {
+            let mut parser = parser(&self.data)?;
+            self.object_offsets
+                .iter()
+                .map(|&offset| {
+                    Ok((parser.parse_object_name(offset)?, LazyParameterObject {
+                        offset,
+                        parsed: OnceCell::new(),
+                    }))
+                })
+                .collect()
+        })
+    }
+
+    /// Number of child lists.
+    pub fn list_count(&self) -> usize {
+        self.list_offsets.len()
+    }
+
+    /// Number of child objects.
+    pub fn object_count(&self) -> usize {
+        self.object_offsets.len()
+    }
+
+    /// Get a child list, parsing its header if needed.
+    pub fn list(&self, name: impl Into<Name>) -> Result<Option<&LazyParameterList>> {
+        Ok(self.lists_map()?.get(&name.into()))
+    }
+
+    /// Iterate over the child lists, parsing their headers if needed.
+    pub fn lists(&self) -> Result<impl Iterator<Item = (&Name, &LazyParameterList)>> {
+        Ok(self.lists_map()?.iter())
+    }
+
+    /// Get a child object, parsing it if needed.
+    pub fn object(&self, name: impl Into<Name>) -> Result<Option<&ParameterObject>> {
+        match self.objects_map()?.get(&name.into()) {
+            Some(object) => {
+                object
+                    .parsed
+                    .get_or_try_init(|| Ok(parser(&self.data)?.parse_object(object.offset)?.1))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Iterate over the names of the child objects, without parsing the
+    /// objects themselves.
+    pub fn object_names(&self) -> Result<impl Iterator<Item = &Name>> {
+        Ok(self.objects_map()?.keys())
+    }
+
+    /// Fully parse this list and everything in it into a regular
+    /// [`ParameterList`].
+    pub fn to_owned(&self) -> Result<ParameterList> {
+        Ok(parser(&self.data)?.parse_list(self.offset, self.depth)?.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lazy() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let pio = ParameterIO::from_binary(&data).unwrap();
+        let lazy = ParameterIO::from_binary_lazy(Arc::new(data)).unwrap();
+        assert_eq!(lazy.version(), pio.version);
+        assert_eq!(lazy.data_type(), pio.data_type);
+        assert_eq!(lazy.param_root().list_count(), pio.lists().len());
+
+        for (name, list) in pio.lists().iter() {
+            let lazy_list = lazy.list(*name).unwrap().unwrap();
+            assert_eq!(lazy_list.object_count(), list.objects.len());
+            for (obj_name, obj) in list.objects.iter() {
+                assert_eq!(lazy_list.object(*obj_name).unwrap(), Some(obj));
+            }
+            assert_eq!(&lazy_list.to_owned().unwrap(), list);
+        }
+        assert!(lazy.object("Missing").unwrap().is_none());
+        assert_eq!(lazy.into_owned().unwrap(), pio);
+
+        let mut truncated = pio.to_binary();
+        let len = truncated.len();
+        truncated.truncate(len / 2);
+        assert!(
+            ParameterIO::from_binary_lazy(Arc::new(truncated))
+                .and_then(LazyParameterIO::into_owned)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use super::{parser::Parser, *};
+
+/// A value involved in a [`MergeConflict`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeValue {
+    /// A parameter.
+    Parameter(Parameter),
+    /// A parameter object.
+    Object(ParameterObject),
+    /// A parameter list.
+    List(ParameterList),
+}
+
+/// A conflicting change found by [`three_way_merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// Names of the lists, objects, and parameter leading to the conflicting
+    /// entry, starting from the root list.
+    pub path:   Vec<Name>,
+    /// Our version of the entry, or `None` if we removed it.
+    pub ours:   Option<MergeValue>,
+    /// Their version of the entry, or `None` if they removed it.
+    pub theirs: Option<MergeValue>,
+}
+
+/// The result of a [`three_way_merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreeWayMergeResult {
+    /// The merged parameter IO. Conflicting entries are resolved in favour of
+    /// `ours`.
+    pub merged: ParameterIO,
+    /// All conflicting changes.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl ThreeWayMergeResult {
+    /// Returns `true` if the merge produced no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merge two sets of changes (`ours` and `theirs`) to a common `base`.
+///
+/// Entries changed (added, modified, or removed) on only one side relative to
+/// `base` are taken from that side. Objects and lists changed on both sides
+/// are merged recursively, so that only parameters (or whole entries removed
+/// on one side and modified on the other) changed differently on both sides
+/// are reported as conflicts. Entries keep the order they have in `ours`, with
+/// entries added in `theirs` appended.
+pub fn three_way_merge(
+    base: &ParameterIO,
+    ours: &ParameterIO,
+    theirs: &ParameterIO,
+) -> ThreeWayMergeResult {
+    let mut conflicts = vec![];
+    let param_root = merge_lists(
+        &base.param_root,
+        &ours.param_root,
+        &theirs.param_root,
+        &mut vec![],
+        &mut conflicts,
+    );
+    fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T) -> T {
+        if ours == base { theirs } else { ours }.clone()
+    }
+    let merged = ParameterIO {
+        version: pick(&base.version, &ours.version, &theirs.version),
+        data_type: pick(&base.data_type, &ours.data_type, &theirs.data_type),
+        param_root,
+    };
+    ThreeWayMergeResult { merged, conflicts }
+}
+
+type MergeFn<V> = fn(Option<&V>, &V, &V, &mut Vec<Name>, &mut Vec<MergeConflict>) -> Option<V>;
+
+fn merge_maps<V: Clone + PartialEq>(
+    base: &ParameterStructureMap<V>,
+    ours: &ParameterStructureMap<V>,
+    theirs: &ParameterStructureMap<V>,
+    path: &mut Vec<Name>,
+    conflicts: &mut Vec<MergeConflict>,
+    merge_both: MergeFn<V>,
+    wrap: fn(V) -> MergeValue,
+) -> ParameterStructureMap<V> {
+    let keys = ours
+        .keys()
+        .chain(theirs.keys().filter(|k| !ours.contains_key(*k)));
+    let mut merged = ParameterStructureMap::default();
+    for key in keys {
+        let (b, o, t) = (base.get(key), ours.get(key), theirs.get(key));
+        let value = if o == t || t == b {
+            o.cloned()
+        } else if o == b {
+            t.cloned()
+        } else {
+            path.push(*key);
+            let value = match (o, t) {
+                (Some(o), Some(t)) => merge_both(b, o, t, path, conflicts),
+                _ => None,
+            };
+            let value = value.or_else(|| {
+                conflicts.push(MergeConflict {
+                    path:   path.clone(),
+                    ours:   o.cloned().map(wrap),
+                    theirs: t.cloned().map(wrap),
+                });
+                o.cloned()
+            });
+            path.pop();
+            value
+        };
+        if let Some(value) = value {
+            merged.insert(*key, value);
+        }
+    }
+    merged
+}
+
+fn merge_objects(
+    base: Option<&ParameterObject>,
+    ours: &ParameterObject,
+    theirs: &ParameterObject,
+    path: &mut Vec<Name>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Option<ParameterObject> {
+    let empty = ParameterObject::default();
+    Some(ParameterObject(merge_maps(
+        &base.unwrap_or(&empty).0,
+        &ours.0,
+        &theirs.0,
+        path,
+        conflicts,
+        |_, _, _, _, _| None,
+        MergeValue::Parameter,
+    )))
+}
+
+fn merge_lists(
+    base: &ParameterList,
+    ours: &ParameterList,
+    theirs: &ParameterList,
+    path: &mut Vec<Name>,
+    conflicts: &mut Vec<MergeConflict>,
+) -> ParameterList {
+    ParameterList {
+        objects: ParameterObjectMap(merge_maps(
+            &base.objects.0,
+            &ours.objects.0,
+            &theirs.objects.0,
+            path,
+            conflicts,
+            merge_objects,
+            MergeValue::Object,
+        )),
+        lists:   ParameterListMap(merge_maps(
+            &base.lists.0,
+            &ours.lists.0,
+            &theirs.lists.0,
+            path,
+            conflicts,
+            |b, o, t, path, conflicts| {
+                Some(merge_lists(
+                    b.unwrap_or(&ParameterList::default()),
+                    o,
+                    t,
+                    path,
+                    conflicts,
+                ))
+            },
+            MergeValue::List,
+        )),
+    }
+}
+
+impl ParameterIO {
+    /// Parse a binary parameter archive as a diff and overlay it onto this one.
+    ///
+    /// Parameters in the diff replace those with the same key, while objects
+    /// and lists in the diff are merged recursively into existing ones with the
+    /// same key. Entries missing from this parameter IO are appended. The
+    /// version and data type of this parameter IO are left unchanged.
+    ///
+    /// No parameter IO is built for the diff: its entries are parsed into
+    /// plain lists, which are only merged once the whole diff has been parsed.
+    /// If the diff is invalid, this parameter IO is left unchanged.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    pub fn merge_from_binary(&mut self, diff_data: impl AsRef<[u8]>) -> Result<()> {
+        let data = diff_data.as_ref();
+        #[cfg(feature = "yaz0")]
+        if data.starts_with(b"Yaz0") {
+            return Parser::new(
+                std::io::Cursor::new(crate::yaz0::decompress(data)?),
+                Default::default(),
+            )?
+            .overlay_onto(self);
+        }
+        Parser::new(std::io::Cursor::new(data), Default::default())?.overlay_onto(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(speed: f32, count: i32) -> ParameterObject {
+        ParameterObject::new()
+            .with_parameter("Speed", Parameter::F32(speed))
+            .with_parameter("Count", Parameter::I32(count))
+    }
+
+    #[test]
+    fn merge() {
+        let base = ParameterIO::new()
+            .with_object("General", object(1.0, 1))
+            .with_list(
+                "Actions",
+                ParameterList::new().with_object("Walk", object(1.0, 1)),
+            );
+        let ours = ParameterIO::new()
+            .with_object("General", object(2.0, 1))
+            .with_list(
+                "Actions",
+                ParameterList::new()
+                    .with_object("Walk", object(1.0, 5))
+                    .with_object("Run", object(3.0, 3)),
+            );
+        let theirs = ParameterIO::new()
+            .with_object("General", object(1.0, 2))
+            .with_object("Extra", object(4.0, 4))
+            .with_list(
+                "Actions",
+                ParameterList::new().with_object("Walk", object(1.0, 6)),
+            );
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert_eq!(result.merged.object("General"), Some(&object(2.0, 2)));
+        assert_eq!(result.merged.object("Extra"), Some(&object(4.0, 4)));
+        let actions = result.merged.list("Actions").unwrap();
+        assert_eq!(actions.object("Run"), Some(&object(3.0, 3)));
+        assert_eq!(actions.object("Walk"), Some(&object(1.0, 5)));
+        assert_eq!(result.conflicts, vec![MergeConflict {
+            path:   vec!["Actions".into(), "Walk".into(), "Count".into()],
+            ours:   Some(MergeValue::Parameter(Parameter::I32(5))),
+            theirs: Some(MergeValue::Parameter(Parameter::I32(6))),
+        }]);
+    }
+
+    #[test]
+    fn remove_and_modify() {
+        let base = ParameterIO::new()
+            .with_object("A", object(1.0, 1))
+            .with_object("B", object(1.0, 1));
+        let ours = ParameterIO::new().with_object("B", object(1.0, 1));
+        let theirs = ParameterIO::new()
+            .with_object("A", object(1.0, 2))
+            .with_object("B", object(1.0, 1));
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert_eq!(result.merged.object("A"), None);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].ours, None);
+
+        let theirs = ParameterIO::new().with_object("A", object(1.0, 1));
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert!(result.merged.objects().is_empty());
+    }
+
+    #[test]
+    fn merge_from_binary() {
+        let mut pio = ParameterIO::new()
+            .with_object("General", object(1.0, 1))
+            .with_list(
+                "Actions",
+                ParameterList::new().with_object("Walk", object(1.0, 1)),
+            );
+        let diff = ParameterIO::new()
+            .with_object(
+                "General",
+                ParameterObject::new().with_parameter("Count", Parameter::I32(2)),
+            )
+            .with_list(
+                "Actions",
+                ParameterList::new()
+                    .with_object(
+                        "Walk",
+                        ParameterObject::new().with_parameter("Count", Parameter::I32(7)),
+                    )
+                    .with_object("Run", object(3.0, 3))
+                    .with_list("New", ParameterList::new().with_object("A", object(0.0, 0))),
+            );
+        pio.merge_from_binary(diff.to_binary()).unwrap();
+        assert_eq!(pio.object("General"), Some(&object(1.0, 2)));
+        let actions = pio.list("Actions").unwrap();
+        assert_eq!(actions.object("Walk"), Some(&object(1.0, 7)));
+        assert_eq!(actions.object("Run"), Some(&object(3.0, 3)));
+        assert_eq!(
+            actions.list("New").and_then(|list| list.object("A")),
+            Some(&object(0.0, 0))
+        );
+        assert!(pio.merge_from_binary(b"AAMP").is_err());
+
+        // A diff that only turns out to be invalid at its last parameter must
+        // not leave the earlier entries merged.
+        let diff = ParameterIO::new()
+            .with_object("General", object(5.0, 5))
+            .with_object(
+                "Late",
+                ParameterObject::new().with_parameter("MergeBad", Parameter::I32(0)),
+            );
+        let mut data = diff.to_binary();
+        let hash = hash_name("MergeBad").to_le_bytes();
+        let entry = data.windows(4).position(|window| window == hash).unwrap();
+        data[entry + 7] = 0xff;
+        let original = pio.clone();
+        assert!(pio.merge_from_binary(&data).is_err());
+        assert_eq!(pio, original);
+    }
+}
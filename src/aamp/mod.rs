@@ -31,18 +31,38 @@
 //! [`ParameterListMap`]) can take either a name or a hash for key-based
 //! operations, and likewise can be indexed by the same. As usual, indexing into
 //! a non-existent key will panic.
+mod actor;
+mod approx;
+mod cache;
+mod cursor;
+#[cfg(feature = "yaml")]
+mod diff;
+mod display;
+#[cfg(feature = "with-serde")]
+mod json;
+mod lazy;
+mod merge;
 mod names;
 mod parser;
 #[cfg(feature = "yaml")]
 mod text;
+mod visit;
 mod writer;
+pub use actor::{ResActor, ACTOR_LINK_USERS};
 use binrw::binrw;
+pub use cache::{CacheStats, ParameterIOCache};
+pub use cursor::ParameterCursor;
 use indexmap::IndexMap;
-pub use names::{get_default_name_table, NameTable};
+pub use lazy::{LazyParameterIO, LazyParameterList};
+pub use merge::{three_way_merge, MergeConflict, MergeValue, ThreeWayMergeResult};
+pub use names::{get_default_name_table, HashResolver, NameTable};
 use num_traits::AsPrimitive;
+pub use parser::{AampWarning, ParseHooks, ParseOptions};
 #[cfg(feature = "with-serde")]
 use serde::{Deserialize, Serialize};
 use smartstring::alias::String;
+pub use visit::{visit, visit_mut, AampVisitor, AampVisitorMut};
+pub use writer::WriteOptions;
 
 use crate::{types::*, util::u24, Error, Result};
 
@@ -50,6 +70,26 @@ type ParameterStructureMap<V> =
     IndexMap<Name, V, std::hash::BuildHasherDefault<rustc_hash::FxHasher>>;
 
 /// CRC hash function matching that used in BOTW.
+///
+/// This is a `const fn`, so names known at compile time can be hashed in
+/// constants, array initializers, and (through named constants) `match`
+/// patterns:
+///
+/// ```
+/// # use roead::aamp::hash_name;
+/// const LINK_TARGET: u32 = hash_name("LinkTarget");
+/// const KNOWN: [u32; 2] = [LINK_TARGET, hash_name("Tags")];
+///
+/// fn describe(hash: u32) -> &'static str {
+///     match hash {
+///         LINK_TARGET => "link target",
+///         _ if KNOWN.contains(&hash) => "known",
+///         _ => "unknown",
+///     }
+/// }
+/// assert_eq!(describe(0x2b1d9958), "link target");
+/// assert_eq!(describe(0xcf8e3b18), "known");
+/// ```
 #[inline]
 pub const fn hash_name(name: &str) -> u32 {
     let mut crc = 0xFFFFFFFF;
@@ -70,9 +110,12 @@ pub const fn hash_name(name: &str) -> u32 {
     !crc
 }
 
-/// A convenient macro for hashing AAMP names. This can help ensure they are
-/// hashed at compile time in contexts where the compiler may not otherwise
-/// realize it is an option.
+/// A convenient macro for hashing AAMP names at compile time.
+///
+/// The hash is computed in a `const` item, so it is always evaluated by the
+/// compiler and never at runtime. This means the name must be a constant
+/// expression; use [`hash_name`](crate::aamp::hash_name) to hash names only
+/// known at runtime.
 ///
 /// # Example
 /// ```rust
@@ -83,11 +126,14 @@ pub const fn hash_name(name: &str) -> u32 {
 /// # }
 /// # let pio = Pio;
 /// pio.list(h!("LinkTargets"));
+/// const NAMES: [u32; 2] = [h!("ActorLink"), h!("LinkTargets")];
+/// ```
 #[macro_export]
 macro_rules! h {
-    ($name:expr) => {
-        $crate::aamp::hash_name($name)
-    };
+    ($name:expr) => {{
+        const H: u32 = $crate::aamp::hash_name($name);
+        H
+    }};
 }
 
 #[cfg(test)]
@@ -96,6 +142,8 @@ fn check_hasher() {
     const HASHED: u32 = hash_name("The Abolition of Man");
     const HASH: u32 = 0x41afa934;
     assert_eq!(HASHED, HASH);
+    const FROM_MACRO: [u32; 2] = [h!("The Abolition of Man"), h!("LinkTarget")];
+    assert_eq!(FROM_MACRO, [HASH, 0x2b1d9958]);
 }
 
 #[derive(Debug)]
@@ -179,6 +227,12 @@ struct ResParameterList {
 /// Note that unlike `agl::utl::Parameter` the name is not stored as part of
 /// the parameter class in order to make the parameter logic simpler and more
 /// efficient.
+///
+/// With the `with-serde` feature, parameters are serialized as externally
+/// tagged enums (e.g. `{"F32": 1.5}`), so every variant keeps its type through
+/// a round trip. The exception is non-finite floats, which JSON cannot
+/// represent: serde_json writes them as `null` and then fails to read them
+/// back. Use [`ParameterIO::to_json_value`] for JSON which supports them.
 #[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 #[allow(clippy::derived_hash_with_manual_eq)]
 #[derive(Debug, Clone)]
@@ -229,28 +283,32 @@ pub enum Parameter {
 
 impl Parameter {
     fn type_name(&self) -> String {
-        match self {
-            Parameter::Bool(_) => "Bool".into(),
-            Parameter::F32(_) => "F32".into(),
-            Parameter::I32(_) => "I32".into(),
-            Parameter::Vec2(_) => "Vec2".into(),
-            Parameter::Vec3(_) => "Vec3".into(),
-            Parameter::Vec4(_) => "Vec4".into(),
-            Parameter::Color(_) => "Color".into(),
-            Parameter::String32(_) => "String32".into(),
-            Parameter::String64(_) => "String64".into(),
-            Parameter::Curve1(_) => "Curve1".into(),
-            Parameter::Curve2(_) => "Curve2".into(),
-            Parameter::Curve3(_) => "Curve3".into(),
-            Parameter::Curve4(_) => "Curve4".into(),
-            Parameter::BufferInt(_) => "BufferInt".into(),
-            Parameter::BufferF32(_) => "BufferF32".into(),
-            Parameter::String256(_) => "String256".into(),
-            Parameter::Quat(_) => "Quat".into(),
-            Parameter::U32(_) => "U32".into(),
-            Parameter::BufferU32(_) => "BufferU32".into(),
-            Parameter::BufferBinary(_) => "BufferBinary".into(),
-            Parameter::StringRef(_) => "StringRef".into(),
+        self.type_str().into()
+    }
+
+    const fn type_str(&self) -> &'static str {
+        match self {
+            Parameter::Bool(_) => "Bool",
+            Parameter::F32(_) => "F32",
+            Parameter::I32(_) => "I32",
+            Parameter::Vec2(_) => "Vec2",
+            Parameter::Vec3(_) => "Vec3",
+            Parameter::Vec4(_) => "Vec4",
+            Parameter::Color(_) => "Color",
+            Parameter::String32(_) => "String32",
+            Parameter::String64(_) => "String64",
+            Parameter::Curve1(_) => "Curve1",
+            Parameter::Curve2(_) => "Curve2",
+            Parameter::Curve3(_) => "Curve3",
+            Parameter::Curve4(_) => "Curve4",
+            Parameter::BufferInt(_) => "BufferInt",
+            Parameter::BufferF32(_) => "BufferF32",
+            Parameter::String256(_) => "String256",
+            Parameter::Quat(_) => "Quat",
+            Parameter::U32(_) => "U32",
+            Parameter::BufferU32(_) => "BufferU32",
+            Parameter::BufferBinary(_) => "BufferBinary",
+            Parameter::StringRef(_) => "StringRef",
         }
     }
 
@@ -1260,7 +1318,11 @@ impl Parameter {
 }
 
 /// Parameter structure name. This is a wrapper around a CRC32 hash.
-#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+///
+/// With the `with-serde` feature, names serialize as hex strings like
+/// `"0x2b1d9958"` in human-readable formats such as JSON, and as plain
+/// integers otherwise. They deserialize from hex strings, decimal strings,
+/// or integers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[binrw::binrw]
 #[brw(little)]
@@ -1329,6 +1391,64 @@ impl std::fmt::Display for Name {
     }
 }
 
+#[cfg(feature = "with-serde")]
+impl Serialize for Name {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&format!("{:#010x}", self.0))
+        } else {
+            serializer.serialize_u32(self.0)
+        }
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        struct NameVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for NameVisitor {
+            type Value = Name;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a name hash as an integer or a hex or decimal string")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> std::result::Result<Name, E> {
+                u32::try_from(v)
+                    .map(Name)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> std::result::Result<Name, E> {
+                u32::try_from(v)
+                    .map(Name)
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> std::result::Result<Name, E> {
+                match v.strip_prefix("0x").or_else(|| v.strip_prefix("0X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16),
+                    None => v.parse(),
+                }
+                .map(Name)
+                .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(NameVisitor)
+        } else {
+            deserializer.deserialize_u32(NameVisitor)
+        }
+    }
+}
+
 impl Name {
     /// The CRC32 hash of the name.
     pub fn hash(&self) -> u32 {
@@ -1344,6 +1464,21 @@ impl Name {
 macro_rules! impl_map_wrapper {
     ($type:tt, $valtype:tt) => {
         impl $type {
+            /// Create an empty map with space for at least `capacity` entries.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                Self(ParameterStructureMap::with_capacity_and_hasher(
+                    capacity,
+                    Default::default(),
+                ))
+            }
+
+            /// Reserve space for at least `additional` more entries.
+            #[inline]
+            pub fn reserve(&mut self, additional: usize) {
+                self.0.reserve(additional)
+            }
+
             /// Return the number of entries.
             #[inline(always)]
             pub fn len(&self) -> usize {
@@ -1362,10 +1497,31 @@ macro_rules! impl_map_wrapper {
                 self.0.insert(key.into(), value);
             }
 
-            /// Insert multiple entries from an iterator.
+            /// Insert multiple entries from an iterator, with keys by name or
+            /// hash.
             #[inline(always)]
-            pub fn extend<I: IntoIterator<Item = (Name, $valtype)>>(&mut self, iter: I) {
-                self.0.extend(iter);
+            pub fn extend<N: Into<Name>, I: IntoIterator<Item = (N, $valtype)>>(
+                &mut self,
+                iter: I,
+            ) {
+                self.0
+                    .extend(iter.into_iter().map(|(key, value)| (key.into(), value)));
+            }
+
+            /// Insert copies of all entries from another map, overwriting
+            /// entries with the same key.
+            #[inline]
+            pub fn extend_from(&mut self, other: &Self) {
+                self.0
+                    .extend(other.0.iter().map(|(key, value)| (*key, value.clone())));
+            }
+
+            /// Insert copies of the entries from another map whose keys are
+            /// not already present.
+            pub fn extend_if_absent(&mut self, other: &Self) {
+                for (key, value) in other.0.iter() {
+                    self.0.entry(*key).or_insert_with(|| value.clone());
+                }
             }
 
             /// Get an entry value by name or hash.
@@ -1386,6 +1542,33 @@ macro_rules! impl_map_wrapper {
                 self.0.entry(key.into())
             }
 
+            /// Rename an entry by name or hash, keeping its position. Fails if
+            /// no entry has the old key or if an entry with the new key
+            /// already exists.
+            pub fn rename<N1: Into<Name>, N2: Into<Name>>(
+                &mut self,
+                old: N1,
+                new: N2,
+            ) -> Result<()> {
+                let (old, new) = (old.into(), new.into());
+                if !self.0.contains_key(&old) {
+                    return Err(Error::InvalidDataD(format!("No entry with key {old}")));
+                }
+                if old == new {
+                    return Ok(());
+                }
+                if self.0.contains_key(&new) {
+                    return Err(Error::InvalidDataD(format!(
+                        "An entry with key {new} already exists"
+                    )));
+                }
+                if let Some((index, _, value)) = self.0.swap_remove_full(&old) {
+                    let (last, _) = self.0.insert_full(new, value);
+                    self.0.swap_indices(index, last);
+                }
+                Ok(())
+            }
+
             /// Iterate entries.
             #[inline(always)]
             pub fn iter(&self) -> impl Iterator<Item = (&Name, &$valtype)> {
@@ -1529,6 +1712,17 @@ pub trait ParameterListing {
     fn set_object<N: Into<Name>>(&mut self, name: N, object: ParameterObject) {
         self.objects_mut().insert(name.into(), object);
     }
+    /// Insert a parameter object by name or hash, replacing and returning any
+    /// existing object with the same key (like
+    /// [`HashMap::insert`](std::collections::HashMap::insert)). A replaced object keeps its
+    /// original position.
+    fn upsert_object<N: Into<Name>>(
+        &mut self,
+        name: N,
+        object: ParameterObject,
+    ) -> Option<ParameterObject> {
+        self.objects_mut().0.insert(name.into(), object)
+    }
 }
 
 /// [`Parameter`] list. This is essentially a dictionary of parameter objects
@@ -1697,4 +1891,536 @@ impl ParameterIO {
         self.param_root = list;
         self
     }
+
+    /// Rename a parameter object in the list at `list_path`, keeping its
+    /// position. The path is made up of list names separated by `/`, starting
+    /// from (but not including) the root list; an empty path refers to the
+    /// root list itself.
+    pub fn rename_object_in(&mut self, list_path: &str, old: &str, new: &str) -> Result<()> {
+        let mut list = &mut self.param_root;
+        for name in list_path.split('/').filter(|name| !name.is_empty()) {
+            list = list
+                .list_mut(name)
+                .ok_or_else(|| Error::InvalidDataD(format!("No parameter list {name}")))?;
+        }
+        list.objects.rename(old, new)
+    }
+
+    /// Get a parameter of an object in a list of the root list, e.g.
+    /// `pio.quick_param("AttackParam", "General", "AttackPower")`. Returns
+    /// `None` if any of them is missing.
+    pub fn quick_param(
+        &self,
+        list: impl Into<Name>,
+        object: impl Into<Name>,
+        param: impl Into<Name>,
+    ) -> Option<&Parameter> {
+        self.param_root
+            .lists
+            .get(list)?
+            .objects
+            .get(object)?
+            .get(param)
+    }
+
+    /// Get a parameter by its full path: the names of the lists from (but not
+    /// including) the root list, then the object name, then the parameter
+    /// name. Returns `None` if the path has fewer than two names or anything
+    /// on it is missing.
+    ///
+    /// ```
+    /// # use roead::aamp::*;
+    /// let pio = ParameterIO::new().with_list(
+    ///     "AttackParam",
+    ///     ParameterList::new().with_object(
+    ///         "General",
+    ///         ParameterObject::new().with_parameter("AttackPower", Parameter::I32(10)),
+    ///     ),
+    /// );
+    /// let power = Parameter::I32(10);
+    /// assert_eq!(
+    ///     pio.quick_param("AttackParam", "General", "AttackPower"),
+    ///     Some(&power)
+    /// );
+    /// assert_eq!(
+    ///     pio.quick_param_deep(&["AttackParam", "General", "AttackPower"]),
+    ///     Some(&power)
+    /// );
+    /// assert_eq!(pio.quick_param_deep(&["General", "AttackPower"]), None);
+    /// ```
+    pub fn quick_param_deep(&self, path: &[&str]) -> Option<&Parameter> {
+        let [lists @ .., object, param] = path else {
+            return None;
+        };
+        let mut list = &self.param_root;
+        for name in lists {
+            list = list.lists.get(*name)?;
+        }
+        list.objects.get(*object)?.get(*param)
+    }
+
+    /// Iterate over every parameter object in the document, depth first, along
+    /// with its path: the names of the lists from (but not including) the root
+    /// list down to its parent list, followed by the object's own name.
+    pub fn iter_objects(&self) -> impl Iterator<Item = (Vec<Name>, &ParameterObject)> {
+        fn collect<'a>(
+            list: &'a ParameterList,
+            path: &mut Vec<Name>,
+            out: &mut Vec<(Vec<Name>, &'a ParameterObject)>,
+        ) {
+            for (name, obj) in list.objects.0.iter() {
+                let mut obj_path = path.clone();
+                obj_path.push(*name);
+                out.push((obj_path, obj));
+            }
+            for (name, child) in list.lists.0.iter() {
+                path.push(*name);
+                collect(child, path, out);
+                path.pop();
+            }
+        }
+        let mut out = vec![];
+        collect(&self.param_root, &mut vec![], &mut out);
+        out.into_iter()
+    }
+
+    /// Find the path of the first object with the given name, in the order of
+    /// [`ParameterIO::iter_objects`]. The path has the same format: the names
+    /// of the lists below the root, followed by the object's own name.
+    ///
+    /// ```
+    /// # use roead::aamp::*;
+    /// let pio = ParameterIO::new().with_list(
+    ///     "List",
+    ///     ParameterList::new().with_object("Target", ParameterObject::new()),
+    /// );
+    /// assert_eq!(
+    ///     pio.find_object("Target"),
+    ///     Some(vec![Name::from("List"), Name::from("Target")])
+    /// );
+    /// assert_eq!(pio.find_object("Missing"), None);
+    /// ```
+    pub fn find_object(&self, name: impl Into<Name>) -> Option<Vec<Name>> {
+        let name = name.into();
+        self.iter_objects()
+            .find(|(path, _)| path.last() == Some(&name))
+            .map(|(path, _)| path)
+    }
+
+    /// Find the paths of all objects with the given name. See
+    /// [`ParameterIO::find_object`].
+    pub fn find_all_objects(&self, name: impl Into<Name>) -> Vec<Vec<Name>> {
+        let name = name.into();
+        self.iter_objects()
+            .filter(|(path, _)| path.last() == Some(&name))
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    /// Every name used in the document, in the same depth-first order as
+    /// [`ParameterIO::iter_objects`]: for each list, its objects (each name
+    /// followed by its parameter names), then its child lists (each name
+    /// followed by its contents). Duplicates are kept. The root list's own
+    /// name is not included.
+    pub fn list_all_names(&self) -> Vec<Name> {
+        fn collect(list: &ParameterList, out: &mut Vec<Name>) {
+            for (name, obj) in list.objects.0.iter() {
+                out.push(*name);
+                out.extend(obj.0.keys());
+            }
+            for (name, child) in list.lists.0.iter() {
+                out.push(*name);
+                collect(child, out);
+            }
+        }
+        let mut out = vec![];
+        collect(&self.param_root, &mut out);
+        out
+    }
+
+    /// The set of all name hashes used in the document. See
+    /// [`ParameterIO::list_all_names`].
+    pub fn list_all_hashes(&self) -> rustc_hash::FxHashSet<u32> {
+        self.list_all_names()
+            .into_iter()
+            .map(|name| name.0)
+            .collect()
+    }
+
+    /// Count the parameters of each type in the whole document, keyed by
+    /// type name (`"Bool"`, `"F32"`, `"BufferF32"`, etc., as in the
+    /// [`Parameter`] variant names). Types which do not occur are omitted.
+    pub fn count_by_type(&self) -> rustc_hash::FxHashMap<&'static str, usize> {
+        let mut counts = rustc_hash::FxHashMap::default();
+        for (_, obj) in self.iter_objects() {
+            for param in obj.0.values() {
+                *counts.entry(param.type_str()).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+
+    /// A 64-bit hash of the whole document, for change detection. Since all
+    /// fields are public, mutations cannot be tracked directly; instead,
+    /// record the fingerprint after loading or saving a document and compare
+    /// it later to find out whether it needs to be written again.
+    ///
+    /// Unlike `==`, the fingerprint depends on the order of entries, as
+    /// reordering changes the binary output too.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the [binary
+    /// form](ParameterIO::to_binary) of the document. Both the format and the
+    /// algorithm are fixed, so a fingerprint can be stored and compared across
+    /// processes, targets and Rust releases.
+    pub fn fingerprint(&self) -> u64 {
+        self.to_binary()
+            .iter()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    /// Iterate mutably over every parameter object in the document. See
+    /// [`ParameterIO::iter_objects`].
+    pub fn iter_objects_mut(&mut self) -> impl Iterator<Item = (Vec<Name>, &mut ParameterObject)> {
+        fn collect<'a>(
+            list: &'a mut ParameterList,
+            path: &mut Vec<Name>,
+            out: &mut Vec<(Vec<Name>, &'a mut ParameterObject)>,
+        ) {
+            for (name, obj) in list.objects.0.iter_mut() {
+                let mut obj_path = path.clone();
+                obj_path.push(*name);
+                out.push((obj_path, obj));
+            }
+            for (name, child) in list.lists.0.iter_mut() {
+                path.push(*name);
+                collect(child, path, out);
+                path.pop();
+            }
+        }
+        let mut out = vec![];
+        collect(&mut self.param_root, &mut vec![], &mut out);
+        out.into_iter()
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    pub(crate) fn normalize_strings(&mut self, form: crate::types::NormalizationForm) {
+        fn normalize_list(list: &mut ParameterList, form: crate::types::NormalizationForm) {
+            for param in list
+                .objects
+                .0
+                .values_mut()
+                .flat_map(|obj| obj.0.values_mut())
+            {
+                match param {
+                    Parameter::StringRef(s) => *s = form.normalize(s).into(),
+                    Parameter::String32(s) => *s = form.normalize(s).as_ref().into(),
+                    Parameter::String64(s) => **s = form.normalize(s).as_ref().into(),
+                    Parameter::String256(s) => **s = form.normalize(s).as_ref().into(),
+                    _ => (),
+                }
+            }
+            for list in list.lists.0.values_mut() {
+                normalize_list(list, form);
+            }
+        }
+        normalize_list(&mut self.param_root, form);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upsert_object() {
+        let mut list = ParameterList::new();
+        let first = ParameterObject::new().with_parameter("Value", Parameter::I32(1));
+        let second = ParameterObject::new().with_parameter("Value", Parameter::I32(2));
+        assert_eq!(list.upsert_object("Obj", first.clone()), None);
+        assert_eq!(list.upsert_object("Obj", second.clone()), Some(first));
+        assert_eq!(list.object("Obj"), Some(&second));
+        assert_eq!(list.objects.len(), 1);
+    }
+
+    #[test]
+    fn extend() {
+        let template = ParameterObject::new()
+            .with_parameter("Speed", Parameter::F32(1.0))
+            .with_parameter("Count", Parameter::I32(1));
+        let overlay = ParameterObject::new()
+            .with_parameter("Count", Parameter::I32(2))
+            .with_parameter("Name", Parameter::StringRef("Mod".into()));
+
+        let mut obj = template.clone();
+        obj.extend_from(&overlay);
+        assert_eq!(obj.get("Speed"), Some(&Parameter::F32(1.0)));
+        assert_eq!(obj.get("Count"), Some(&Parameter::I32(2)));
+        assert_eq!(obj.get("Name"), Some(&Parameter::StringRef("Mod".into())));
+
+        let mut obj = template.clone();
+        obj.extend_if_absent(&overlay);
+        assert_eq!(obj.get("Count"), Some(&Parameter::I32(1)));
+        assert_eq!(obj.get("Name"), Some(&Parameter::StringRef("Mod".into())));
+
+        let mut objects = ParameterObjectMap::default();
+        objects.extend([("A", template.clone())]);
+        objects.extend_if_absent(
+            &[("A", overlay.clone()), ("B", overlay)]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(objects.get("A"), Some(&template));
+        assert_eq!(objects.len(), 2);
+    }
+
+    #[test]
+    fn rename() {
+        let obj = |v| ParameterObject::new().with_parameter("Value", Parameter::I32(v));
+        let mut pio = ParameterIO::new().with_list(
+            "Actions",
+            ParameterList::new().with_list(
+                "Walk",
+                ParameterList::new()
+                    .with_object("A", obj(1))
+                    .with_object("B", obj(2))
+                    .with_object("C", obj(3)),
+            ),
+        );
+        pio.rename_object_in("Actions/Walk", "A", "D").unwrap();
+        let walk = &pio.list("Actions").unwrap().lists["Walk"];
+        let keys: Vec<Name> = walk.objects.iter().map(|(k, _)| *k).collect();
+        assert_eq!(keys, vec!["D".into(), "B".into(), "C".into()]);
+        assert_eq!(walk.object("D"), Some(&obj(1)));
+        assert!(pio.rename_object_in("Actions/Walk", "A", "E").is_err());
+        assert!(pio.rename_object_in("Actions/Walk", "B", "C").is_err());
+        assert!(pio.rename_object_in("Actions/Run", "B", "E").is_err());
+
+        pio.lists_mut().rename("Actions", "Behaviors").unwrap();
+        assert!(pio.list("Actions").is_none());
+        assert!(pio.list("Behaviors").is_some());
+    }
+
+    #[test]
+    fn list_all_names() {
+        let obj = |v| ParameterObject::new().with_parameter("Scale", Parameter::F32(v));
+        let pio = ParameterIO::new()
+            .with_object("General", obj(1.0))
+            .with_list(
+                "Actions",
+                ParameterList::new().with_object("Walk", obj(2.0)),
+            );
+        let names: Vec<Name> = ["General", "Scale", "Actions", "Walk", "Scale"]
+            .into_iter()
+            .map(Name::from)
+            .collect();
+        assert_eq!(pio.list_all_names(), names);
+        let hashes = pio.list_all_hashes();
+        assert_eq!(hashes.len(), 4);
+        assert!(hashes.contains(&hash_name("Walk")));
+        assert!(ParameterIO::new().list_all_names().is_empty());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let mut obj = ParameterObject::with_capacity(100);
+        assert!(obj.is_empty());
+        assert!(obj.0.capacity() >= 100);
+        obj.insert("A", Parameter::I32(1));
+        obj.reserve(200);
+        assert!(obj.0.capacity() >= 201);
+        assert!(ParameterObjectMap::with_capacity(10).0.capacity() >= 10);
+        assert!(ParameterListMap::with_capacity(10).0.capacity() >= 10);
+    }
+
+    #[test]
+    fn count_by_type() {
+        let pio = ParameterIO::new()
+            .with_object(
+                "A",
+                ParameterObject::new()
+                    .with_parameter("F", Parameter::F32(1.0))
+                    .with_parameter("B", Parameter::Bool(true)),
+            )
+            .with_list(
+                "L",
+                ParameterList::new().with_object(
+                    "C",
+                    ParameterObject::new().with_parameter("F", Parameter::F32(2.0)),
+                ),
+            );
+        let counts = pio.count_by_type();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["F32"], 2);
+        assert_eq!(counts["Bool"], 1);
+        assert!(ParameterIO::new().count_by_type().is_empty());
+    }
+
+    #[test]
+    fn fingerprint() {
+        let mut pio =
+            ParameterIO::from_binary(std::fs::read("test/aamp/Lizalfos.bphysics").unwrap())
+                .unwrap();
+        let original = pio.fingerprint();
+        assert_eq!(pio.clone().fingerprint(), original);
+
+        let (_, obj) = pio.iter_objects_mut().next().unwrap();
+        let (_, param) = obj.0.iter_mut().next().unwrap();
+        let old = std::mem::replace(param, Parameter::StringRef("changed".into()));
+        assert_ne!(pio.fingerprint(), original);
+        let (_, obj) = pio.iter_objects_mut().next().unwrap();
+        *obj.0.values_mut().next().unwrap() = old;
+        assert_eq!(pio.fingerprint(), original);
+
+        // Same bits, different type.
+        let a = ParameterIO::new().with_object(
+            "A",
+            ParameterObject::new().with_parameter("B", Parameter::I32(1)),
+        );
+        let b = ParameterIO::new().with_object(
+            "A",
+            ParameterObject::new().with_parameter("B", Parameter::U32(1)),
+        );
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        // Fingerprints may be stored, so they must not change between builds.
+        assert_eq!(a.fingerprint(), 7347271811961994166);
+    }
+
+    #[test]
+    fn iter_objects() {
+        let obj = |v| ParameterObject::new().with_parameter("Scale", Parameter::F32(v));
+        let mut pio = ParameterIO::new()
+            .with_object("General", obj(1.0))
+            .with_list(
+                "Actions",
+                ParameterList::new()
+                    .with_object("General", obj(2.0))
+                    .with_list(
+                        "Walk",
+                        ParameterList::new().with_object("General", obj(3.0)),
+                    ),
+            );
+        let paths: Vec<Vec<Name>> = pio.iter_objects().map(|(path, _)| path).collect();
+        assert_eq!(paths, vec![
+            vec!["General".into()],
+            vec!["Actions".into(), "General".into()],
+            vec!["Actions".into(), "Walk".into(), "General".into()],
+        ]);
+        assert_eq!(pio.find_all_objects("General"), paths);
+        assert!(pio.find_all_objects("Walk").is_empty());
+
+        for (_, obj) in pio.iter_objects_mut() {
+            if let Some(Parameter::F32(scale)) = obj.get_mut("Scale") {
+                *scale *= 2.0;
+            }
+        }
+        let scales: Vec<f32> = pio
+            .iter_objects()
+            .filter_map(|(_, obj)| obj.get("Scale")?.as_f32().ok())
+            .collect();
+        assert_eq!(scales, [2.0, 4.0, 6.0]);
+    }
+
+    #[cfg(feature = "with-serde")]
+    #[test]
+    fn name_serde() {
+        let name = Name(0xabcd1234);
+        assert_eq!(serde_json::to_string(&name).unwrap(), r#""0xabcd1234""#);
+        for json in [r#""0xabcd1234""#, r#""2882343476""#, "2882343476"] {
+            assert_eq!(serde_json::from_str::<Name>(json).unwrap(), name);
+        }
+        assert!(serde_json::from_str::<Name>(r#""0xnope""#).is_err());
+        assert!(serde_json::from_str::<Name>("4294967296").is_err());
+
+        let pio = ParameterIO::from_binary(std::fs::read("test/aamp/GameRomHorse.bxml").unwrap())
+            .unwrap();
+        let json = serde_json::to_string(&pio).unwrap();
+        assert!(json.contains(&format!("{:#010x}", hash_name("LinkTarget"))));
+        assert_eq!(serde_json::from_str::<ParameterIO>(&json).unwrap(), pio);
+    }
+
+    #[cfg(feature = "with-serde")]
+    #[test]
+    fn serde_roundtrip() {
+        let curve = Curve {
+            a: 1,
+            b: 2,
+            floats: std::array::from_fn(|i| i as f32 * 0.1),
+        };
+        let obj = ParameterObject::new()
+            .with_parameter("Bool", Parameter::Bool(true))
+            .with_parameter("F32", Parameter::F32(0.1))
+            .with_parameter("I32", Parameter::I32(-5))
+            .with_parameter("Vec2", Parameter::Vec2(Vector2f { x: 1.0, y: 2.0 }))
+            .with_parameter(
+                "Vec3",
+                Parameter::Vec3(Vector3f {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                }),
+            )
+            .with_parameter(
+                "Vec4",
+                Parameter::Vec4(Vector4f {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                    t: 4.0,
+                }),
+            )
+            .with_parameter(
+                "Color",
+                Parameter::Color(Color {
+                    r: 0.5,
+                    g: 0.25,
+                    b: 0.125,
+                    a: 1.0,
+                }),
+            )
+            .with_parameter("String32", Parameter::String32("A".into()))
+            .with_parameter("String64", Parameter::String64(Box::new("B".into())))
+            .with_parameter("Curve1", Parameter::Curve1(Box::new([curve])))
+            .with_parameter("Curve2", Parameter::Curve2(Box::new([curve; 2])))
+            .with_parameter("Curve3", Parameter::Curve3(Box::new([curve; 3])))
+            .with_parameter("Curve4", Parameter::Curve4(Box::new([curve; 4])))
+            .with_parameter("BufferInt", Parameter::BufferInt(vec![-1, 0, 1]))
+            .with_parameter("BufferF32", Parameter::BufferF32(vec![0.5, -0.5]))
+            .with_parameter("String256", Parameter::String256(Box::new("C".into())))
+            .with_parameter(
+                "Quat",
+                Parameter::Quat(Quat {
+                    a: 1.0,
+                    b: 0.0,
+                    c: 0.0,
+                    d: 0.0,
+                }),
+            )
+            .with_parameter("U32", Parameter::U32(u32::MAX))
+            .with_parameter("BufferU32", Parameter::BufferU32(vec![u32::MAX]))
+            .with_parameter("BufferBinary", Parameter::BufferBinary(vec![0, 0x7F, 0xFF]))
+            .with_parameter("StringRef", Parameter::StringRef("Hello, world".into()));
+        let pio = ParameterIO::new()
+            .with_object("Obj", obj.clone())
+            .with_list("List", ParameterList::new().with_object("Nested", obj));
+        let json = serde_json::to_string(&pio).unwrap();
+        let parsed: ParameterIO = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, pio);
+        // Variants must keep their types, not just compare equal.
+        for (name, param) in pio.object("Obj").unwrap().iter() {
+            assert_eq!(
+                parsed.object("Obj").unwrap()[*name].type_name(),
+                param.type_name()
+            );
+        }
+
+        // JSON has no representation for non-finite floats.
+        let nan = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new().with_parameter("F32", Parameter::F32(f32::NAN)),
+        );
+        let json = serde_json::to_string(&nan).unwrap();
+        assert!(serde_json::from_str::<ParameterIO>(&json).is_err());
+    }
 }
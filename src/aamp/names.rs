@@ -218,3 +218,25 @@ static DEFAULT_NAME_TABLE: Lazy<Arc<NameTable<'static>>> =
 pub fn get_default_name_table() -> &'static Lazy<Arc<NameTable<'static>>> {
     &DEFAULT_NAME_TABLE
 }
+
+/// Source of names for hashes, e.g. a list of names taken from a specific
+/// file, for use with [`ParameterIO::from_binary_with_resolver`] and
+/// [`ParameterIO::to_text_with_resolver`].
+pub trait HashResolver {
+    /// Get the name for a hash, if known.
+    fn resolve(&self, hash: u32) -> Option<&str>;
+}
+
+impl HashResolver for NameTable<'_> {
+    fn resolve(&self, hash: u32) -> Option<&str> {
+        self.get_name(hash, 0, 0).map(|name| name.as_ref())
+    }
+}
+
+impl<S: AsRef<str>, H: std::hash::BuildHasher> HashResolver
+    for std::collections::HashMap<u32, S, H>
+{
+    fn resolve(&self, hash: u32) -> Option<&str> {
+        self.get(&hash).map(|name| name.as_ref())
+    }
+}
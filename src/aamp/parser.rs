@@ -1,14 +1,143 @@
-use std::io::{Read, Seek};
+use std::io::{BufRead, Read, Seek};
 
 use binrw::prelude::*;
 
 use super::*;
-use crate::{Error, Result};
+use crate::{types::StringEncoding, Error, Result};
+
+/// Options for parsing a [`ParameterIO`] from binary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Apply NFC normalization to all string parameters, so that strings
+    /// compare equal regardless of which normalization form the file used.
+    ///
+    /// This is only available with the `unicode-normalization` feature.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_strings: bool,
+    /// Encoding of string parameters. Archives which are not flagged as UTF-8
+    /// can only be parsed with an encoding other than UTF-8.
+    pub string_encoding: StringEncoding,
+    /// Reject archives with data beyond the file size given in the header.
+    /// By default any such data, e.g. alignment padding, is ignored.
+    pub strict: bool,
+}
+
+type PreParseHook<'a> = Box<dyn Fn(&[u8]) -> Result<()> + 'a>;
+type PostParseHook<'a> = Box<dyn Fn(&ParameterIO) -> Result<()> + 'a>;
+type ErrorHook<'a> = Box<dyn Fn(&Error) + 'a>;
+
+/// Callbacks run around [`ParameterIO::from_binary_with_hooks`], e.g. for
+/// signature checks before parsing or validation of the parsed document.
+#[derive(Default)]
+pub struct ParseHooks<'a> {
+    /// Runs on the raw input before parsing. Returning an error aborts parsing.
+    pub pre_parse:  Option<PreParseHook<'a>>,
+    /// Runs on the parsed document. Returning an error discards it.
+    pub post_parse: Option<PostParseHook<'a>>,
+    /// Runs on any error from parsing or from the other hooks, before it is
+    /// returned.
+    pub on_error:   Option<ErrorHook<'a>>,
+}
+
+impl<'a> ParseHooks<'a> {
+    /// Create an empty set of hooks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the pre-parse hook.
+    pub fn with_pre_parse(mut self, hook: impl Fn(&[u8]) -> Result<()> + 'a) -> Self {
+        self.pre_parse = Some(Box::new(hook));
+        self
+    }
+
+    /// Set the post-parse hook.
+    pub fn with_post_parse(mut self, hook: impl Fn(&ParameterIO) -> Result<()> + 'a) -> Self {
+        self.post_parse = Some(Box::new(hook));
+        self
+    }
+
+    /// Set the error hook.
+    pub fn with_on_error(mut self, hook: impl Fn(&Error) + 'a) -> Self {
+        self.on_error = Some(Box::new(hook));
+        self
+    }
+}
+
+impl std::fmt::Debug for ParseHooks<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParseHooks")
+            .field("pre_parse", &self.pre_parse.is_some())
+            .field("post_parse", &self.post_parse.is_some())
+            .field("on_error", &self.on_error.is_some())
+            .finish()
+    }
+}
+
+/// A problem encountered while parsing a parameter archive with
+/// [`ParameterIO::from_binary_lenient`]. Offsets are from the start of the
+/// (decompressed) archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AampWarning {
+    /// The archive header is unusable, so nothing could be parsed.
+    InvalidHeader {
+        /// Description of the problem.
+        message: std::string::String,
+    },
+    /// A parameter has a type which does not exist. It was skipped.
+    UnknownParameterType {
+        /// Offset of the parameter.
+        offset: usize,
+        /// The raw type value.
+        type_byte: u8,
+    },
+    /// A parameter could not be read, e.g. because its data is out of bounds.
+    /// It was skipped.
+    InvalidParameter {
+        /// Offset of the parameter.
+        offset:  usize,
+        /// Description of the problem.
+        message: std::string::String,
+    },
+    /// An object header could not be read. The object was skipped.
+    InvalidObject {
+        /// Offset of the object.
+        offset:  usize,
+        /// Description of the problem.
+        message: std::string::String,
+    },
+    /// A list header could not be read. The list was skipped.
+    InvalidList {
+        /// Offset of the list.
+        offset:  usize,
+        /// Description of the problem.
+        message: std::string::String,
+    },
+}
 
 impl ParameterIO {
-    /// Read a parameter archive from a binary reader.
+    /// Read a parameter archive from a binary reader. The parser does many
+    /// small reads, so unbuffered readers should be wrapped or passed to
+    /// [`ParameterIO::from_bufread`] instead.
     pub fn read<R: Read + Seek>(reader: R) -> Result<ParameterIO> {
-        Parser::new(reader)?.parse()
+        Parser::new(reader, Default::default())?.parse()
+    }
+
+    /// Read a parameter archive from a buffered reader, e.g. a
+    /// [`std::io::BufReader`] wrapping a file, without first reading the whole
+    /// file into memory.
+    ///
+    /// Unlike [`ParameterIO::from_binary`], this does not decompress Yaz0
+    /// data.
+    ///
+    /// ```
+    /// # use roead::aamp::ParameterIO;
+    /// let file = std::fs::File::open("test/aamp/Lizalfos.bphysics")?;
+    /// let pio = ParameterIO::from_bufread(std::io::BufReader::new(file))?;
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn from_bufread<R: BufRead + Seek>(reader: R) -> Result<ParameterIO> {
+        Parser::new(reader, Default::default())?.parse()
     }
 
     /// Load a parameter archive from binary data.
@@ -16,31 +145,267 @@ impl ParameterIO {
     /// **Note**: If and only if the `yaz0` feature is enabled, this function
     /// automatically decompresses the data when necessary.
     pub fn from_binary(data: impl AsRef<[u8]>) -> Result<ParameterIO> {
+        Self::from_binary_at(data, 0)
+    }
+
+    /// Load a parameter archive embedded at position `base` in a larger
+    /// buffer. All offsets in the archive are taken as relative to `base`.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    pub fn from_binary_at(data: impl AsRef<[u8]>, base: usize) -> Result<ParameterIO> {
+        let data = data
+            .as_ref()
+            .get(base..)
+            .ok_or(Error::InvalidData("Parameter archive base out of bounds"))?;
+        Self::from_binary_with_options(data, &Default::default())
+    }
+
+    /// Load a parameter archive from binary data, failing if the data is
+    /// longer than the file size given in the archive header. [`from_binary`]
+    /// instead ignores such trailing data.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    ///
+    /// [`from_binary`]: ParameterIO::from_binary
+    pub fn from_binary_strict(data: impl AsRef<[u8]>) -> Result<ParameterIO> {
+        Self::from_binary_with_options(data, &ParseOptions {
+            strict: true,
+            ..Default::default()
+        })
+    }
+
+    /// Load a parameter archive from binary data, looking up the names of all
+    /// its lists, objects and parameters with the given resolver.
+    ///
+    /// A parameter IO only stores name hashes, so the resolved names are
+    /// returned in a separate [`NameTable`] (without the Breath of the Wild
+    /// strings) next to the document, to be passed to
+    /// [`ParameterIO::to_text_with_resolver`] or used for lookups. The
+    /// [default name table](get_default_name_table) is not modified. Names
+    /// which do not match their hash are ignored.
+    pub fn from_binary_with_resolver(
+        data: impl AsRef<[u8]>,
+        resolver: Option<&dyn HashResolver>,
+    ) -> Result<(ParameterIO, NameTable<'static>)> {
+        fn resolve_list(list: &ParameterList, resolver: &dyn HashResolver, table: &NameTable) {
+            let names =
+                list.lists
+                    .iter()
+                    .map(|(name, _)| name)
+                    .chain(list.objects.iter().flat_map(|(name, obj)| {
+                        std::iter::once(name).chain(obj.iter().map(|(name, _)| name))
+                    }));
+            for name in names {
+                if let Some(string) = resolver.resolve(name.0)
+                    && hash_name(string) == name.0
+                {
+                    table.add_name_with_hash(string.to_owned(), name.0);
+                }
+            }
+            for list in list.lists.0.values() {
+                resolve_list(list, resolver, table);
+            }
+        }
+
+        let pio = Self::from_binary(data)?;
+        let table = NameTable::new(false);
+        if let Some(resolver) = resolver {
+            resolve_list(&pio.param_root, resolver, &table);
+        }
+        Ok((pio, table))
+    }
+
+    /// Load a parameter archive from binary data, running the given hooks
+    /// before and after parsing. The pre-parse hook receives `data` as
+    /// passed, i.e. before any decompression.
+    pub fn from_binary_with_hooks(
+        data: impl AsRef<[u8]>,
+        hooks: &ParseHooks,
+    ) -> Result<ParameterIO> {
+        let data = data.as_ref();
+        let result = (|| {
+            if let Some(pre_parse) = &hooks.pre_parse {
+                pre_parse(data)?;
+            }
+            let pio = Self::from_binary(data)?;
+            if let Some(post_parse) = &hooks.post_parse {
+                post_parse(&pio)?;
+            }
+            Ok(pio)
+        })();
+        if let (Err(err), Some(on_error)) = (&result, &hooks.on_error) {
+            on_error(err);
+        }
+        result
+    }
+
+    /// Load as much of a possibly corrupted parameter archive as possible.
+    /// Parameters, objects and lists which cannot be parsed are skipped and
+    /// reported as warnings, so the returned document may be incomplete. If
+    /// the header itself is unusable, an empty document is returned.
+    ///
+    /// This is meant for recovery tools; use [`ParameterIO::from_binary`] to
+    /// find out whether an archive is valid.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    pub fn from_binary_lenient(data: impl AsRef<[u8]>) -> (ParameterIO, Vec<AampWarning>) {
+        fn parse<R: Read + Seek>(reader: R) -> (ParameterIO, Vec<AampWarning>) {
+            let mut warnings = vec![];
+            let invalid_header = |err: Error| {
+                AampWarning::InvalidHeader {
+                    message: err.to_string(),
+                }
+            };
+            let mut parser = match Parser::new(reader, Default::default()) {
+                Ok(parser) => parser,
+                Err(err) => return (ParameterIO::new(), vec![invalid_header(err)]),
+            };
+            let mut pio = ParameterIO::new();
+            match parser.pio_info() {
+                Ok((version, data_type)) => {
+                    pio.version = version;
+                    pio.data_type = data_type;
+                }
+                Err(err) => warnings.push(invalid_header(err)),
+            }
+            match parser.root_offset() {
+                Ok(offset) => {
+                    if let Some((_, root)) = parser.parse_list_lenient(offset, 0, &mut warnings) {
+                        pio.param_root = root;
+                    }
+                }
+                Err(err) => warnings.push(invalid_header(err)),
+            }
+            (pio, warnings)
+        }
+
+        let data = data.as_ref();
+        #[cfg(feature = "yaz0")]
+        {
+            if data.starts_with(b"Yaz0") {
+                return match crate::yaz0::decompress(data) {
+                    Ok(data) => parse(std::io::Cursor::new(data)),
+                    Err(err) => {
+                        (ParameterIO::new(), vec![AampWarning::InvalidHeader {
+                            message: err.to_string(),
+                        }])
+                    }
+                };
+            }
+        }
+        parse(std::io::Cursor::new(data))
+    }
+
+    /// Load a parameter archive from binary data using the given options.
+    ///
+    /// **Note**: If and only if the `yaz0` feature is enabled, this function
+    /// automatically decompresses the data when necessary.
+    pub fn from_binary_with_options(
+        data: impl AsRef<[u8]>,
+        options: &ParseOptions,
+    ) -> Result<ParameterIO> {
         #[cfg(feature = "yaz0")]
         {
             if data.as_ref().starts_with(b"Yaz0") {
-                return Parser::new(std::io::Cursor::new(crate::yaz0::decompress(
-                    data.as_ref(),
-                )?))?
+                return Parser::new(
+                    std::io::Cursor::new(crate::yaz0::decompress(data.as_ref())?),
+                    *options,
+                )?
                 .parse();
             }
         }
-        Parser::new(std::io::Cursor::new(data.as_ref()))?.parse()
+        Parser::new(std::io::Cursor::new(data.as_ref()), *options)?.parse()
+    }
+}
+
+/// Maximum nesting depth of parameter lists. This is far deeper than in any
+/// real archive and guards against lists which (indirectly) contain
+/// themselves.
+pub(super) const MAX_LIST_DEPTH: usize = 128;
+
+#[inline]
+fn rel_offset(base: u32, rel: u32) -> Result<u32> {
+    base.checked_add(rel)
+        .ok_or(Error::InvalidData("Parameter archive offset out of range"))
+}
+
+impl ResHeader {
+    /// Whether the header looks like a big endian header read as little
+    /// endian: either the version is a byte-swapped 2, or the little endian
+    /// flag is unset and every count is only plausible when byte-swapped.
+    fn is_byte_swapped(&self) -> bool {
+        let counts = [self.list_count, self.object_count, self.param_count];
+        self.version != 2 && self.version.swap_bytes() == 2
+            || self.flags & 1 == 0 && counts.iter().all(|&c| c > 0xFFFFFF)
+    }
+}
+
+/// Collect `count` parsed entries into a map allocated at its final size.
+/// Collecting through `Result` gives the map no size hint, so it would
+/// otherwise be grown repeatedly.
+fn collect_map<V>(
+    count: u16,
+    mut parse: impl FnMut(u32) -> Result<(Name, V)>,
+) -> Result<ParameterStructureMap<V>> {
+    let mut map =
+        ParameterStructureMap::with_capacity_and_hasher(count as usize, Default::default());
+    for i in 0..count as u32 {
+        let (name, value) = parse(i)?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// A diff parsed by [`Parser::parse_overlay`], waiting to be merged.
+struct ListOverlay {
+    objects: Vec<(Name, Vec<(Name, Parameter)>)>,
+    lists:   Vec<(Name, ListOverlay)>,
+}
+
+impl ListOverlay {
+    /// Parameters replace those with the same name in existing objects, and
+    /// child lists are merged recursively into existing ones. Objects and
+    /// lists which do not exist yet are appended.
+    fn apply(self, target: &mut ParameterList) {
+        for (name, params) in self.objects {
+            target.objects.0.entry(name).or_default().0.extend(params);
+        }
+        for (name, overlay) in self.lists {
+            overlay.apply(target.lists.0.entry(name).or_default());
+        }
     }
 }
 
-struct Parser<R: Read + Seek> {
+pub(super) struct Parser<R: Read + Seek> {
     reader: R,
+    len: u64,
     header: ResHeader,
-    opts:   binrw::ReadOptions,
+    opts: binrw::ReadOptions,
+    options: ParseOptions,
 }
 
 impl<R: Read + Seek> Parser<R> {
-    fn new(mut reader: R) -> Result<Self> {
-        if reader.stream_len()? < 0x30 {
+    pub(super) fn new(mut reader: R, options: ParseOptions) -> Result<Self> {
+        let len = reader.stream_len()?;
+        if len < 0x30 {
             return Err(Error::InvalidData("Incomplete parameter archive"));
         }
         let header = ResHeader::read(&mut reader)?;
+        if header.is_byte_swapped() {
+            return Err(Error::InvalidData(
+                "Parameter archive appears to be big endian, but only little endian archives are \
+                 supported",
+            ));
+        }
+        if options.strict && len != header.file_size as u64 {
+            return Err(Error::InvalidDataD(format!(
+                "Parameter archive is {len:#x} bytes, but its header gives a size of {:#x}",
+                header.file_size
+            )));
+        }
         if header.version != 2 {
             return Err(Error::InvalidData(
                 "Only version 2 parameter archives are supported",
@@ -51,33 +416,57 @@ impl<R: Read + Seek> Parser<R> {
                 "Only little endian parameter archives are supported",
             ));
         }
-        if header.flags & 1 << 1 != 1 << 1 {
+        if header.flags & 1 << 1 != 1 << 1 && options.string_encoding == StringEncoding::Utf8 {
             return Err(Error::InvalidData(
                 "Only UTF-8 parameter archives are supported",
             ));
         }
         Ok(Self {
             reader,
+            // Only the first `file_size` bytes belong to the archive.
+            len: if header.file_size >= 0x30 {
+                len.min(header.file_size as u64)
+            } else {
+                len
+            },
             header,
             opts: binrw::ReadOptions::default().with_endian(binrw::Endian::Little),
+            options,
         })
     }
 
+    /// Offset of the root list.
+    pub(super) fn root_offset(&self) -> Result<u32> {
+        rel_offset(self.header.pio_offset, 0x30)
+    }
+
+    /// The data version and type of the parameter IO.
+    pub(super) fn pio_info(&mut self) -> Result<(u32, String)> {
+        self.seek(0x30)?;
+        Ok((self.header.pio_version, self.read_null_string()?))
+    }
+
     fn parse(&mut self) -> Result<ParameterIO> {
-        let (root_name, param_root) = self.parse_list(self.header.pio_offset + 0x30)?;
+        let (root_name, param_root) = self.parse_list(self.root_offset()?, 0)?;
         if root_name != ROOT_KEY {
             Err(Error::InvalidData(
                 "No param root found in parameter archive",
             ))
         } else {
-            Ok(ParameterIO {
+            #[allow(unused_mut)]
+            let mut pio = ParameterIO {
                 version: self.header.pio_version,
                 data_type: {
                     self.seek(0x30)?;
                     self.read_null_string()?
                 },
                 param_root,
-            })
+            };
+            #[cfg(feature = "unicode-normalization")]
+            if self.options.normalize_strings {
+                pio.normalize_strings(crate::types::NormalizationForm::Nfc);
+            }
+            Ok(pio)
         }
     }
 
@@ -93,16 +482,33 @@ impl<R: Read + Seek> Parser<R> {
     }
 
     #[inline]
-    fn read_null_string(&mut self) -> Result<String> {
-        let mut string_ = [0u8; 0x256];
+    fn read_null_bytes(&mut self, max_len: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
         let mut c: u8 = self.read()?;
-        let mut len = 0;
-        while c != 0 {
-            string_[len] = c;
-            len += 1;
+        while c != 0 && bytes.len() < max_len {
+            bytes.push(c);
             c = self.read()?;
         }
-        Ok(unsafe { std::str::from_utf8_unchecked(&string_[..len]) }.into())
+        Ok(bytes)
+    }
+
+    #[inline]
+    fn read_null_string(&mut self) -> Result<String> {
+        let bytes = self.read_null_bytes(usize::MAX)?;
+        self.options.string_encoding.decode(&bytes)
+    }
+
+    fn read_safe_string<const N: usize>(&mut self) -> Result<FixedSafeString<N>> {
+        if self.options.string_encoding == StringEncoding::Utf8 {
+            return self.read();
+        }
+        let bytes = self.read_null_bytes(N)?;
+        let string = self.options.string_encoding.decode(&bytes)?;
+        let mut len = string.len().min(N);
+        while !string.is_char_boundary(len) {
+            len -= 1;
+        }
+        Ok(string[..len].into())
     }
 
     #[inline]
@@ -114,20 +520,33 @@ impl<R: Read + Seek> Parser<R> {
         Ok(val)
     }
 
+    /// Read the element count stored before the buffer at `offset`, checking
+    /// that the buffer fits in the archive.
+    fn read_buffer_size<T>(&mut self, offset: u32) -> Result<usize> {
+        let size_offset = offset
+            .checked_sub(4)
+            .ok_or(Error::InvalidData("Invalid parameter buffer offset"))?;
+        let size = self.read_at::<u32>(size_offset)?;
+        if offset as u64 + size as u64 * std::mem::size_of::<T>() as u64 > self.len {
+            return Err(Error::InvalidData("Parameter buffer out of bounds"));
+        }
+        Ok(size as usize)
+    }
+
     fn read_buffer<T: BinRead<Args = ()> + Copy>(&mut self, offset: u32) -> Result<Vec<T>> {
-        let size = self.read_at::<u32>(offset - 4)?;
+        let size = self.read_buffer_size::<T>(offset)?;
         let buf = Vec::<T>::read_options(
             &mut self.reader,
             &self.opts,
-            binrw::VecArgs::builder().count(size as usize).finalize(),
+            binrw::VecArgs::builder().count(size).finalize(),
         )?;
         Ok(buf)
     }
 
     #[inline]
     fn read_float_buffer(&mut self, offset: u32) -> Result<Vec<f32>> {
-        let size = self.read_at::<u32>(offset - 4)?;
-        let mut buf = Vec::<f32>::with_capacity(size as usize);
+        let size = self.read_buffer_size::<f32>(offset)?;
+        let mut buf = Vec::<f32>::with_capacity(size);
         for _ in 0..size {
             buf.push(self.read()?);
         }
@@ -137,7 +556,7 @@ impl<R: Read + Seek> Parser<R> {
     fn parse_parameter(&mut self, offset: u32) -> Result<(Name, Parameter)> {
         self.seek(offset)?;
         let info: ResParameter = self.read()?;
-        let data_offset = info.data_rel_offset.as_u32() * 4 + offset;
+        let data_offset = rel_offset(offset, info.data_rel_offset.as_u32() * 4)?;
         self.seek(data_offset)?;
         let value = match info.type_ {
             Type::Bool => Parameter::Bool(self.read::<u32>()? != 0),
@@ -153,43 +572,209 @@ impl<R: Read + Seek> Parser<R> {
             Type::Curve2 => Parameter::Curve2(self.read()?),
             Type::Curve3 => Parameter::Curve3(self.read()?),
             Type::Curve4 => Parameter::Curve4(self.read()?),
-            Type::String32 => Parameter::String32(self.read()?),
-            Type::String64 => Parameter::String64(self.read()?),
-            Type::String256 => Parameter::String256(self.read()?),
+            Type::String32 => Parameter::String32(self.read_safe_string()?),
+            Type::String64 => Parameter::String64(Box::new(self.read_safe_string()?)),
+            Type::String256 => Parameter::String256(Box::new(self.read_safe_string()?)),
             Type::StringRef => Parameter::StringRef(self.read_null_string()?),
             Type::BufferInt => Parameter::BufferInt(self.read_buffer::<i32>(data_offset)?),
             Type::BufferU32 => Parameter::BufferU32(self.read_buffer::<u32>(data_offset)?),
-            Type::BufferF32 => Parameter::BufferF32(self.read_float_buffer(offset)?),
+            Type::BufferF32 => Parameter::BufferF32(self.read_float_buffer(data_offset)?),
             Type::BufferBinary => Parameter::BufferBinary(self.read_buffer::<u8>(data_offset)?),
         };
         Ok((info.name, value))
     }
 
-    fn parse_object(&mut self, offset: u32) -> Result<(Name, ParameterObject)> {
+    /// Read the name of the object at `offset` without parsing its
+    /// parameters.
+    pub(super) fn parse_object_name(&mut self, offset: u32) -> Result<Name> {
+        self.seek(offset)?;
+        let info: ResParameterObj = self.read()?;
+        Ok(info.name)
+    }
+
+    pub(super) fn parse_object(&mut self, offset: u32) -> Result<(Name, ParameterObject)> {
         self.seek(offset)?;
         let info: ResParameterObj = self.read()?;
-        let offset = info.params_rel_offset as u32 * 4 + offset;
-        let params = (0..info.param_count)
-            .map(|i| self.parse_parameter(offset + 0x8 * i as u32))
+        let offset = rel_offset(offset, info.params_rel_offset as u32 * 4)?;
+        let params = collect_map(info.param_count, |i| {
+            self.parse_parameter(rel_offset(offset, 0x8 * i)?)
+        })?;
+        Ok((info.name, ParameterObject(params)))
+    }
+
+    /// Read the name of the list at `offset` and the offsets of its child
+    /// lists and objects, without parsing them.
+    pub(super) fn parse_list_header(&mut self, offset: u32) -> Result<(Name, Vec<u32>, Vec<u32>)> {
+        self.seek(offset)?;
+        let info: ResParameterList = self.read()?;
+        let lists_offset = rel_offset(offset, info.lists_rel_offset as u32 * 4)?;
+        let objects_offset = rel_offset(offset, info.objects_rel_offset as u32 * 4)?;
+        let lists = (0..info.list_count)
+            .map(|i| rel_offset(lists_offset, 0xC * i as u32))
+            .collect::<Result<_>>()?;
+        let objects = (0..info.object_count)
+            .map(|i| rel_offset(objects_offset, 0x8 * i as u32))
             .collect::<Result<_>>()?;
-        Ok((info.name, params))
+        Ok((info.name, lists, objects))
     }
 
-    fn parse_list(&mut self, offset: u32) -> Result<(Name, ParameterList)> {
+    pub(super) fn parse_list(
+        &mut self,
+        offset: u32,
+        depth: usize,
+    ) -> Result<(Name, ParameterList)> {
+        if depth > MAX_LIST_DEPTH {
+            return Err(Error::InvalidData("Parameter lists nested too deeply"));
+        }
         self.seek(offset)?;
         let info: ResParameterList = self.read()?;
-        let lists_offset = info.lists_rel_offset as u32 * 4 + offset;
-        let objects_offset = info.objects_rel_offset as u32 * 4 + offset;
+        let lists_offset = rel_offset(offset, info.lists_rel_offset as u32 * 4)?;
+        let objects_offset = rel_offset(offset, info.objects_rel_offset as u32 * 4)?;
         let plist = ParameterList {
-            lists:   (0..info.list_count)
-                .map(|i| self.parse_list(lists_offset + 0xC * i as u32))
-                .collect::<Result<_>>()?,
-            objects: (0..info.object_count)
-                .map(|i| self.parse_object(objects_offset + 0x8 * i as u32))
-                .collect::<Result<_>>()?,
+            lists:   ParameterListMap(collect_map(info.list_count, |i| {
+                self.parse_list(rel_offset(lists_offset, 0xC * i)?, depth + 1)
+            })?),
+            objects: ParameterObjectMap(collect_map(info.object_count, |i| {
+                self.parse_object(rel_offset(objects_offset, 0x8 * i)?)
+            })?),
         };
         Ok((info.name, plist))
     }
+
+    /// Parse the archive as a diff and merge it into `pio`. See
+    /// [`ParameterIO::merge_from_binary`]. The whole diff is parsed before
+    /// `pio` is modified, so `pio` is left untouched if parsing fails.
+    pub(super) fn overlay_onto(&mut self, pio: &mut ParameterIO) -> Result<()> {
+        let root_offset = self.root_offset()?;
+        self.seek(root_offset)?;
+        let root: ResParameterList = self.read()?;
+        if root.name != ROOT_KEY {
+            return Err(Error::InvalidData(
+                "No param root found in parameter archive",
+            ));
+        }
+        let (_, overlay) = self.parse_overlay(root_offset, 0)?;
+        overlay.apply(&mut pio.param_root);
+        Ok(())
+    }
+
+    /// Parse the list at `offset` as a diff. Entries are kept in plain vectors
+    /// in file order rather than in maps, as they are only applied once.
+    fn parse_overlay(&mut self, offset: u32, depth: usize) -> Result<(Name, ListOverlay)> {
+        if depth > MAX_LIST_DEPTH {
+            return Err(Error::InvalidData("Parameter lists nested too deeply"));
+        }
+        let (name, lists, objects) = self.parse_list_header(offset)?;
+        let mut overlay = ListOverlay {
+            objects: Vec::with_capacity(objects.len()),
+            lists:   Vec::with_capacity(lists.len()),
+        };
+        for offset in objects {
+            self.seek(offset)?;
+            let info: ResParameterObj = self.read()?;
+            let params_offset = rel_offset(offset, info.params_rel_offset as u32 * 4)?;
+            let mut params = Vec::with_capacity(info.param_count as usize);
+            for i in 0..info.param_count as u32 {
+                params.push(self.parse_parameter(rel_offset(params_offset, 0x8 * i)?)?);
+            }
+            overlay.objects.push((info.name, params));
+        }
+        for offset in lists {
+            overlay.lists.push(self.parse_overlay(offset, depth + 1)?);
+        }
+        Ok((name, overlay))
+    }
+
+    /// Like [`Parser::parse_object`], but skipping parameters which cannot be
+    /// parsed. Only fails if the object header cannot be read.
+    fn parse_object_lenient(
+        &mut self,
+        offset: u32,
+        warnings: &mut Vec<AampWarning>,
+    ) -> Result<(Name, ParameterObject)> {
+        self.seek(offset)?;
+        let info: ResParameterObj = self.read()?;
+        let params_offset = rel_offset(offset, info.params_rel_offset as u32 * 4)?;
+        let mut params = ParameterStructureMap::with_capacity_and_hasher(
+            info.param_count as usize,
+            Default::default(),
+        );
+        for i in 0..info.param_count as u32 {
+            let offset = rel_offset(params_offset, 0x8 * i)?;
+            match self.parse_parameter(offset) {
+                Ok((name, param)) => {
+                    params.insert(name, param);
+                }
+                Err(err) => {
+                    // The type is the last byte of the parameter header.
+                    let type_byte = rel_offset(offset, 7).and_then(|o| self.read_at::<u8>(o));
+                    warnings.push(match type_byte {
+                        Ok(type_byte) if type_byte > Type::StringRef as u8 => {
+                            AampWarning::UnknownParameterType {
+                                offset: offset as usize,
+                                type_byte,
+                            }
+                        }
+                        _ => {
+                            AampWarning::InvalidParameter {
+                                offset:  offset as usize,
+                                message: err.to_string(),
+                            }
+                        }
+                    });
+                }
+            }
+        }
+        Ok((info.name, ParameterObject(params)))
+    }
+
+    /// Like [`Parser::parse_list`], but skipping anything which cannot be
+    /// parsed. Returns `None`, after recording a warning, if the list header
+    /// itself cannot be read.
+    fn parse_list_lenient(
+        &mut self,
+        offset: u32,
+        depth: usize,
+        warnings: &mut Vec<AampWarning>,
+    ) -> Option<(Name, ParameterList)> {
+        let invalid_list = |message: std::string::String| {
+            AampWarning::InvalidList {
+                offset: offset as usize,
+                message,
+            }
+        };
+        if depth > MAX_LIST_DEPTH {
+            warnings.push(invalid_list("Parameter lists nested too deeply".into()));
+            return None;
+        }
+        let (name, lists, objects) = match self.parse_list_header(offset) {
+            Ok(header) => header,
+            Err(err) => {
+                warnings.push(invalid_list(err.to_string()));
+                return None;
+            }
+        };
+        let mut plist = ParameterList::new();
+        for offset in lists {
+            if let Some((name, list)) = self.parse_list_lenient(offset, depth + 1, warnings) {
+                plist.lists.0.insert(name, list);
+            }
+        }
+        for offset in objects {
+            match self.parse_object_lenient(offset, warnings) {
+                Ok((name, obj)) => {
+                    plist.objects.0.insert(name, obj);
+                }
+                Err(err) => {
+                    warnings.push(AampWarning::InvalidObject {
+                        offset:  offset as usize,
+                        message: err.to_string(),
+                    })
+                }
+            }
+        }
+        Some((name, plist))
+    }
 }
 
 #[cfg(test)]
@@ -211,4 +796,229 @@ mod tests {
             ParameterIO::from_binary(data).unwrap();
         }
     }
+
+    #[test]
+    fn from_binary_at() {
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let mut embedded = b"SOMEHEADER\0\0\0\0\0\0".to_vec();
+        embedded.extend_from_slice(&data);
+        assert_eq!(
+            ParameterIO::from_binary_at(&embedded, 0x10).unwrap(),
+            ParameterIO::from_binary(&data).unwrap()
+        );
+        assert!(ParameterIO::from_binary_at(&embedded, 0).is_err());
+        assert!(ParameterIO::from_binary_at(&data, data.len() + 1).is_err());
+    }
+
+    #[test]
+    fn resolver() {
+        let names: rustc_hash::FxHashMap<u32, &str> = [
+            (hash_name("ResolverTestObj"), "ResolverTestObj"),
+            (hash_name("ResolverTestParam"), "ResolverTestParam"),
+            (hash_name("ResolverTestWrong"), "Wrong"),
+        ]
+        .into_iter()
+        .collect();
+        let pio = ParameterIO::new().with_object(
+            "ResolverTestObj",
+            ParameterObject::new()
+                .with_parameter("ResolverTestParam", Parameter::I32(1))
+                .with_parameter("ResolverTestWrong", Parameter::I32(2)),
+        );
+        let data = pio.to_binary();
+        let (parsed, table) = ParameterIO::from_binary_with_resolver(&data, Some(&names)).unwrap();
+        assert_eq!(parsed, pio);
+        let resolve = |s: &str| table.resolve(Name::from(s).0);
+        assert_eq!(resolve("ResolverTestObj"), Some("ResolverTestObj"));
+        assert_eq!(resolve("ResolverTestParam"), Some("ResolverTestParam"));
+        assert_eq!(resolve("ResolverTestWrong"), None);
+        // The default name table is left alone.
+        let obj = Name::from("ResolverTestObj");
+        assert_eq!(obj.to_string(), obj.0.to_string());
+
+        #[cfg(feature = "yaml")]
+        {
+            let text = parsed.to_text_with_resolver(&table);
+            assert!(text.contains("ResolverTestObj: !obj"));
+            assert!(text.contains("ResolverTestParam: 1"));
+            assert!(!text.contains("Wrong"));
+            assert_eq!(ParameterIO::from_text(text).unwrap(), pio);
+            // Wrong names are also ignored when they come from the resolver
+            // directly.
+            assert!(!parsed.to_text_with_resolver(&names).contains("Wrong"));
+        }
+
+        let (parsed, table) = ParameterIO::from_binary_with_resolver(&data, None).unwrap();
+        assert_eq!(parsed, pio);
+        assert_eq!(table.resolve(obj.0), None);
+    }
+
+    #[test]
+    fn lenient() {
+        let good = ParameterObject::new().with_parameter("LenientGood", Parameter::I32(1));
+        let pio = ParameterIO::new()
+            .with_object("LenientObj", good.clone())
+            .with_object(
+                "LenientBadParams",
+                ParameterObject::new()
+                    .with_parameter("LenientKept", Parameter::F32(1.0))
+                    .with_parameter("LenientBadType", Parameter::I32(2)),
+            )
+            .with_object(
+                "LenientBadObj",
+                ParameterObject::new().with_parameter("LenientLost", Parameter::I32(3)),
+            );
+        let mut data = pio.to_binary();
+        let find = |data: &[u8], name: &str| {
+            let hash = hash_name(name).to_le_bytes();
+            data.windows(4).position(|w| w == hash).unwrap()
+        };
+        let bad_type = find(&data, "LenientBadType");
+        data[bad_type + 7] = 0xFF;
+        let bad_obj = find(&data, "LenientBadObj");
+        data[bad_obj + 4..bad_obj + 6].copy_from_slice(&0xFFFFu16.to_le_bytes());
+        assert!(ParameterIO::from_binary(&data).is_err());
+
+        let (parsed, warnings) = ParameterIO::from_binary_lenient(&data);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.contains(&AampWarning::UnknownParameterType {
+            offset: bad_type,
+            type_byte: 0xFF,
+        }));
+        // The object with the bad parameter offset is kept, but empty.
+        assert!(
+            warnings
+                .iter()
+                .any(|w| matches!(w, AampWarning::InvalidParameter { .. }))
+        );
+        assert!(parsed.object("LenientBadObj").unwrap().is_empty());
+        assert_eq!(parsed.object("LenientObj"), Some(&good));
+        let kept = parsed.object("LenientBadParams").unwrap();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept.get("LenientKept"), Some(&Parameter::F32(1.0)));
+
+        let (empty, warnings) = ParameterIO::from_binary_lenient(b"AAMP");
+        assert_eq!(empty, ParameterIO::new());
+        assert!(matches!(warnings[..], [AampWarning::InvalidHeader { .. }]));
+    }
+
+    #[test]
+    fn from_bufread() {
+        let file = std::fs::File::open("test/aamp/GameRomHorse.bxml").unwrap();
+        let pio = ParameterIO::from_bufread(std::io::BufReader::new(file)).unwrap();
+        assert_eq!(
+            pio,
+            ParameterIO::from_binary(std::fs::read("test/aamp/GameRomHorse.bxml").unwrap())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn trailing_data() {
+        let mut data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        let pio = ParameterIO::from_binary_strict(&data).unwrap();
+        data.resize((data.len() / 0x1000 + 2) * 0x1000, 0);
+        assert_eq!(ParameterIO::from_binary(&data).unwrap(), pio);
+        assert!(ParameterIO::from_binary_strict(&data).is_err());
+    }
+
+    #[test]
+    fn big_endian() {
+        let mut data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        for field in data[0x4..0x30].chunks_exact_mut(4) {
+            field.reverse();
+        }
+        match ParameterIO::from_binary(&data) {
+            Err(Error::InvalidData(msg)) => assert!(msg.contains("big endian")),
+            other => panic!("Unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+        let errors = std::cell::Cell::new(0);
+        let hooks = ParseHooks::new()
+            .with_pre_parse(|data| {
+                if data.len() < 0x100 {
+                    Err(Error::InvalidData("Too short"))
+                } else {
+                    Ok(())
+                }
+            })
+            .with_post_parse(|pio| {
+                pio.object("LinkTarget")
+                    .map(|_| ())
+                    .ok_or(Error::InvalidData("Not an actor link"))
+            })
+            .with_on_error(|_| errors.set(errors.get() + 1));
+        assert_eq!(
+            ParameterIO::from_binary_with_hooks(&data, &hooks).unwrap(),
+            ParameterIO::from_binary(&data).unwrap()
+        );
+        assert!(ParameterIO::from_binary_with_hooks(&data[..0x40], &hooks).is_err());
+        let physics = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        assert!(ParameterIO::from_binary_with_hooks(physics, &hooks).is_err());
+        assert_eq!(errors.get(), 2);
+    }
+
+    #[test]
+    fn float_buffer() {
+        let pio = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter("Buf", Parameter::BufferF32(vec![1.0, 2.5, -3.0])),
+        );
+        crate::assert_aamp_roundtrip!(pio);
+    }
+
+    #[test]
+    fn corrupt_data() {
+        let data = std::fs::read("test/aamp/GameRomHorse.bxml").unwrap();
+        for len in 0..data.len() {
+            let _ = ParameterIO::from_binary(&data[..len]);
+        }
+        for i in 0..data.len() {
+            for byte in [0x00, 0x7F, 0xFF] {
+                let mut corrupt = data.clone();
+                corrupt[i] = byte;
+                let _ = ParameterIO::from_binary(&corrupt);
+            }
+        }
+
+        // A root list whose only child list is itself.
+        let mut cyclic = ParameterIO::new().to_binary();
+        cyclic[0x34..0x38].copy_from_slice(&[0, 0, 1, 0]);
+        assert!(ParameterIO::from_binary(&cyclic).is_err());
+    }
+
+    #[cfg(feature = "shift-jis")]
+    #[test]
+    fn shift_jis() {
+        let sjis = [0x83, 0x65, 0x83, 0x58, 0x83, 0x67];
+        let pio = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter("Ref", Parameter::StringRef("abcdef".into()))
+                .with_parameter("Fixed", Parameter::String32("ghijkl".into())),
+        );
+        let mut data = pio.to_binary();
+        for placeholder in [b"abcdef", b"ghijkl"] {
+            let pos = data
+                .windows(placeholder.len())
+                .position(|w| w == placeholder)
+                .unwrap();
+            data[pos..pos + sjis.len()].copy_from_slice(&sjis);
+        }
+        data[8] &= !(1 << 1);
+        assert!(ParameterIO::from_binary(&data).is_err());
+        let options = ParseOptions {
+            string_encoding: StringEncoding::ShiftJis,
+            ..Default::default()
+        };
+        let pio = ParameterIO::from_binary_with_options(&data, &options).unwrap();
+        let obj = pio.object("Obj").unwrap();
+        assert_eq!(obj.get("Ref").unwrap().as_str().unwrap(), "テスト");
+        assert_eq!(obj.get("Fixed").unwrap().as_str().unwrap(), "テスト");
+    }
 }
@@ -7,16 +7,48 @@ use crate::{types::*, yaml::*, Error, Result};
 impl ParameterIO {
     /// Parse ParameterIO from YAML text.
     pub fn from_text(text: impl AsRef<str>) -> Result<Self> {
-        let tree = Tree::parse(text.as_ref())?;
+        let tree = parse_yaml(text.as_ref())?;
         let root_ref = tree.root_ref()?;
         read_parameter_io(&root_ref)
     }
 
     /// Serialize the parameter IO to YAML.
     pub fn to_text(&self) -> std::string::String {
+        self.write_text(None)
+    }
+
+    /// Serialize the parameter IO to YAML, looking up names with `resolver`
+    /// before falling back to the [default name table](get_default_name_table).
+    /// Names from the resolver which do not match their hash are ignored.
+    ///
+    /// This is meant to be used with the table returned by
+    /// [`ParameterIO::from_binary_with_resolver`]:
+    ///
+    /// ```
+    /// # use roead::aamp::*;
+    /// let pio = ParameterIO::new().with_object(
+    ///     "DocTestObj",
+    ///     ParameterObject::new().with_parameter("DocTestParam", Parameter::I32(1)),
+    /// );
+    /// let names: std::collections::HashMap<u32, &str> = ["DocTestObj", "DocTestParam"]
+    ///     .into_iter()
+    ///     .map(|name| (hash_name(name), name))
+    ///     .collect();
+    /// let (pio, table) = ParameterIO::from_binary_with_resolver(pio.to_binary(), Some(&names))?;
+    /// let text = pio.to_text_with_resolver(&table);
+    /// assert!(text.contains("DocTestObj: !obj"));
+    /// assert!(text.contains("DocTestParam: 1"));
+    /// assert!(!pio.to_text().contains("DocTestObj"));
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn to_text_with_resolver(&self, resolver: &dyn HashResolver) -> std::string::String {
+        self.write_text(Some(resolver))
+    }
+
+    fn write_text(&self, resolver: Option<&dyn HashResolver>) -> std::string::String {
         let mut tree = Tree::default();
         tree.reserve(10000);
-        write_parameter_io(&mut tree, self)
+        write_parameter_io(&mut tree, self, resolver)
             .expect("ParameterIO should serialize to YAML without error");
         tree.emit()
             .expect("ParameterIO should serialize to YAML without error")
@@ -348,15 +380,34 @@ fn write_parameter<'a, 't>(
     Ok(())
 }
 
+/// Look up a name with the resolver if there is one, and otherwise with the
+/// default name table.
+fn lookup_name(
+    resolver: Option<&dyn HashResolver>,
+    hash: u32,
+    index: usize,
+    parent_hash: u32,
+) -> Option<&str> {
+    resolver
+        .and_then(|resolver| resolver.resolve(hash))
+        .filter(|name| hash_name(name) == hash)
+        .or_else(|| {
+            get_default_name_table()
+                .get_name(hash, index, parent_hash)
+                .map(|name| name.as_ref())
+        })
+}
+
 fn write_parameter_object<'a, 't>(
     pobj: &ParameterObject,
     parent_hash: u32,
     mut node: NodeRef<'a, 't, '_, &'t mut Tree<'a>>,
+    resolver: Option<&dyn HashResolver>,
 ) -> Result<()> {
     node.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in pobj.0.iter().enumerate() {
         let mut child = node.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = lookup_name(resolver, key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -375,6 +426,7 @@ fn write_parameter_list<'a, 't>(
     plist: &ParameterList,
     parent_hash: u32,
     mut node: NodeRef<'a, 't, '_, &'t mut Tree<'a>>,
+    resolver: Option<&dyn HashResolver>,
 ) -> Result<()> {
     node.change_type(ryml::NodeType::Map)?;
     let mut objects = node.append_child()?;
@@ -382,7 +434,7 @@ fn write_parameter_list<'a, 't>(
     objects.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in plist.objects.0.iter().enumerate() {
         let mut child = objects.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = lookup_name(resolver, key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -391,14 +443,14 @@ fn write_parameter_list<'a, 't>(
         } else {
             child.set_key(&lexical::to_string(key.0))?;
         }
-        write_parameter_object(val, key.0, child)?;
+        write_parameter_object(val, key.0, child, resolver)?;
     }
     let mut lists = node.append_child()?;
     lists.set_key("lists")?;
     lists.change_type(ryml::NodeType::Map)?;
     for (i, (key, val)) in plist.lists.0.iter().enumerate() {
         let mut child = lists.append_child()?;
-        if let Some(name) = get_default_name_table().get_name(key.0, i, parent_hash) {
+        if let Some(name) = lookup_name(resolver, key.0, i, parent_hash) {
             if lexical::parse::<u64, _>(name.as_bytes()).is_ok() {
                 let ty = child.node_type()?;
                 child.set_type_flags(ty | ryml::NodeType::WipKeyDquo)?;
@@ -407,13 +459,17 @@ fn write_parameter_list<'a, 't>(
         } else {
             child.set_key(&lexical::to_string(key.0))?;
         }
-        write_parameter_list(val, key.0, child)?;
+        write_parameter_list(val, key.0, child, resolver)?;
     }
     node.set_val_tag("!list")?;
     Ok(())
 }
 
-fn write_parameter_io(tree: &mut Tree<'_>, pio: &ParameterIO) -> Result<()> {
+fn write_parameter_io(
+    tree: &mut Tree<'_>,
+    pio: &ParameterIO,
+    resolver: Option<&dyn HashResolver>,
+) -> Result<()> {
     let mut root = tree.root_ref_mut()?;
     root.change_type(ryml::NodeType::Map)?;
     root.set_val_tag("!io")?;
@@ -422,7 +478,7 @@ fn write_parameter_io(tree: &mut Tree<'_>, pio: &ParameterIO) -> Result<()> {
     root.get_mut("type")?.set_val(&pio.data_type)?;
     let mut param_root = root.append_child()?;
     param_root.set_key("param_root")?;
-    write_parameter_list(&pio.param_root, ROOT_KEY.0, param_root)?;
+    write_parameter_list(&pio.param_root, ROOT_KEY.0, param_root, resolver)?;
     Ok(())
 }
 
@@ -0,0 +1,179 @@
+//! Depth-first traversal of parameter IOs with visitor traits.
+use std::ops::ControlFlow;
+
+use super::*;
+
+/// Callbacks for [`visit`]. Every method does nothing by default.
+///
+/// Returning [`ControlFlow::Break`] from [`visit_list`](Self::visit_list) or
+/// [`visit_object`](Self::visit_object) skips the children of that list or
+/// object; siblings are still visited.
+pub trait AampVisitor {
+    /// Called for each parameter list, before its children.
+    fn visit_list(&mut self, _name: &Name, _list: &ParameterList) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for each parameter object, before its parameters.
+    fn visit_object(&mut self, _name: &Name, _object: &ParameterObject) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for each parameter.
+    fn visit_parameter(&mut self, _name: &Name, _param: &Parameter) {}
+}
+
+/// Mutable counterpart of [`AampVisitor`], for [`visit_mut`]. Children are
+/// visited after the visitor has had a chance to modify their parent, so
+/// anything added to a list or object is visited as well.
+pub trait AampVisitorMut {
+    /// Called for each parameter list, before its children.
+    fn visit_list(&mut self, _name: &Name, _list: &mut ParameterList) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for each parameter object, before its parameters.
+    fn visit_object(&mut self, _name: &Name, _object: &mut ParameterObject) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called for each parameter.
+    fn visit_parameter(&mut self, _name: &Name, _param: &mut Parameter) {}
+}
+
+fn visit_list(name: &Name, list: &ParameterList, visitor: &mut impl AampVisitor) {
+    if visitor.visit_list(name, list).is_break() {
+        return;
+    }
+    for (name, object) in list.objects.iter() {
+        if visitor.visit_object(name, object).is_continue() {
+            for (name, param) in object.iter() {
+                visitor.visit_parameter(name, param);
+            }
+        }
+    }
+    for (name, list) in list.lists.iter() {
+        visit_list(name, list, visitor);
+    }
+}
+
+fn visit_list_mut(name: &Name, list: &mut ParameterList, visitor: &mut impl AampVisitorMut) {
+    if visitor.visit_list(name, list).is_break() {
+        return;
+    }
+    for (name, object) in list.objects.iter_mut() {
+        if visitor.visit_object(name, object).is_continue() {
+            for (name, param) in object.iter_mut() {
+                visitor.visit_parameter(name, param);
+            }
+        }
+    }
+    for (name, list) in list.lists.iter_mut() {
+        visit_list_mut(name, list, visitor);
+    }
+}
+
+/// Traverse a parameter IO depth first, starting with the root list (named
+/// `param_root`). Within each list, objects and their parameters are visited
+/// before child lists.
+///
+/// ```
+/// # use roead::aamp::*;
+/// # use std::ops::ControlFlow;
+/// /// Find all objects with more than 10 parameters.
+/// struct LargeObjects(Vec<Name>);
+///
+/// impl AampVisitor for LargeObjects {
+///     fn visit_object(&mut self, name: &Name, object: &ParameterObject) -> ControlFlow<()> {
+///         if object.len() > 10 {
+///             self.0.push(*name);
+///         }
+///         ControlFlow::Break(())
+///     }
+/// }
+///
+/// let pio = ParameterIO::from_binary(std::fs::read("test/aamp/Lizalfos.bphysics")?)?;
+/// let mut visitor = LargeObjects(vec![]);
+/// visit(&pio, &mut visitor);
+/// # Ok::<(), roead::Error>(())
+/// ```
+pub fn visit(pio: &ParameterIO, visitor: &mut impl AampVisitor) {
+    visit_list(&ROOT_KEY, &pio.param_root, visitor);
+}
+
+/// Traverse a parameter IO depth first with a visitor which can modify it in
+/// place. The traversal order is the same as for [`visit`].
+pub fn visit_mut(pio: &mut ParameterIO, visitor: &mut impl AampVisitorMut) {
+    visit_list_mut(&ROOT_KEY, &mut pio.param_root, visitor);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pio() -> ParameterIO {
+        let object = |value| ParameterObject::new().with_parameter("Value", Parameter::I32(value));
+        ParameterIO::new().with_object("A", object(1)).with_list(
+            "List",
+            ParameterList::new()
+                .with_object("B", object(2))
+                .with_list("Skipped", ParameterList::new().with_object("C", object(3))),
+        )
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<std::string::String>);
+
+    impl AampVisitor for Recorder {
+        fn visit_list(&mut self, name: &Name, _list: &ParameterList) -> ControlFlow<()> {
+            self.0.push(format!("list {name}"));
+            if *name == Name::from("Skipped") {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+
+        fn visit_object(&mut self, name: &Name, _object: &ParameterObject) -> ControlFlow<()> {
+            self.0.push(format!("object {name}"));
+            ControlFlow::Continue(())
+        }
+
+        fn visit_parameter(&mut self, _name: &Name, param: &Parameter) {
+            self.0.push(format!("{param:?}"));
+        }
+    }
+
+    struct Doubler;
+
+    impl AampVisitorMut for Doubler {
+        fn visit_parameter(&mut self, _name: &Name, param: &mut Parameter) {
+            if let Parameter::I32(value) = param {
+                *value *= 2;
+            }
+        }
+    }
+
+    #[test]
+    fn visitors() {
+        let mut pio = pio();
+        let mut recorder = Recorder::default();
+        visit(&pio, &mut recorder);
+        let names = ["param_root", "A", "List", "B", "Skipped"].map(Name::from);
+        assert_eq!(recorder.0, [
+            format!("list {}", names[0]),
+            format!("object {}", names[1]),
+            "I32(1)".into(),
+            format!("list {}", names[2]),
+            format!("object {}", names[3]),
+            "I32(2)".into(),
+            format!("list {}", names[4]),
+        ]);
+
+        visit_mut(&mut pio, &mut Doubler);
+        assert_eq!(
+            pio.quick_param_deep(&["List", "Skipped", "C", "Value"]),
+            Some(&Parameter::I32(6))
+        );
+    }
+}
@@ -13,11 +13,50 @@ use rustc_hash::FxHashMap;
 use super::*;
 use crate::{util::align, Result};
 
+/// Options for serializing a [`ParameterIO`] to binary.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Write the parameters of identical parameter objects only once and have
+    /// every copy of the object refer to them. Parameter data is always
+    /// deduplicated, so this only saves the space of the parameter entries.
+    pub dedup_identical_objects: bool,
+    /// Apply NFC normalization to all string parameters before writing.
+    ///
+    /// This is only available with the `unicode-normalization` feature.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_strings: bool,
+}
+
 impl ParameterIO {
     /// Serialize the parameter IO to binary using the given writer.
     pub fn write<W: Write + Seek>(&self, writer: W) -> Result<()> {
+        self.write_with_options(writer, &WriteOptions::default())
+    }
+
+    /// Serialize the parameter IO to binary using the given writer and
+    /// options.
+    ///
+    /// Entries refer to their children with 16-bit offsets in units of 4
+    /// bytes, so this fails with
+    /// [`Error::InvalidData`](crate::Error::InvalidData) if an object has so
+    /// many parameters before it that those offsets would overflow.
+    pub fn write_with_options<W: Write + Seek>(
+        &self,
+        writer: W,
+        options: &WriteOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "unicode-normalization")]
+        if options.normalize_strings {
+            let mut pio = self.clone();
+            pio.normalize_strings(crate::types::NormalizationForm::Nfc);
+            return pio.write_with_options(writer, &WriteOptions {
+                normalize_strings: false,
+                ..*options
+            });
+        }
         let mut ctx = WriteContext {
             writer,
+            options: *options,
             list_count: Default::default(),
             object_count: Default::default(),
             param_count: Default::default(),
@@ -26,16 +65,25 @@ impl ParameterIO {
             offsets: Default::default(),
             string_offsets: Default::default(),
             buffer_offsets: Default::default(),
+            duplicate_objects: Default::default(),
+            param_array_offsets: Default::default(),
         };
         ctx.writer.seek(SeekFrom::Start(0x30))?;
         ctx.writer.write_le(&self.data_type.as_bytes())?;
         ctx.writer.write_le(&0u8)?;
+        // Every offset in the format is stored in units of 4 bytes relative to
+        // a list, object or parameter entry, and all entries are 8 or 12 bytes
+        // long. Aligning the root list therefore aligns all structures and
+        // parameter data to 4 bytes.
         ctx.align()?;
         let pio_offset = ctx.writer.stream_position()?;
         let root = &self.param_root;
 
         ctx.write_lists(self)?;
         ctx.write_objects(root)?;
+        if options.dedup_identical_objects {
+            ctx.find_duplicate_objects(root, &mut Default::default());
+        }
         ctx.collect_parameters(self);
         ctx.write_parameters(root)?;
 
@@ -68,12 +116,36 @@ impl ParameterIO {
     }
 
     /// Serialize the parameter IO to in-memory bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the document is too large for the format; see
+    /// [`ParameterIO::write_with_options`].
     pub fn to_binary(&self) -> Vec<u8> {
+        self.to_binary_with_options(&WriteOptions::default())
+    }
+
+    /// Serialize the parameter IO to in-memory bytes using the given options.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the document is too large for the format; see
+    /// [`ParameterIO::write_with_options`].
+    pub fn to_binary_with_options(&self, options: &WriteOptions) -> Vec<u8> {
         let mut buf = Vec::new();
-        self.write(Cursor::new(&mut buf))
+        self.write_with_options(Cursor::new(&mut buf), options)
             .expect("Parameter IO should serialize to binary without error");
         buf
     }
+
+    /// Compute the exact size of the binary parameter archive that
+    /// [`ParameterIO::to_binary`] would produce, without writing any data.
+    pub fn compute_binary_size(&self) -> usize {
+        let mut counter = crate::util::SizeCounter::default();
+        self.write(&mut counter)
+            .expect("Parameter IO size should compute without error");
+        counter.len as usize
+    }
 }
 
 #[inline]
@@ -93,8 +165,19 @@ fn hash_param_data(param: &Parameter) -> u64 {
     hasher.finish()
 }
 
+#[inline]
+fn hash_object(object: &ParameterObject) -> u64 {
+    let mut hasher = rustc_hash::FxHasher::default();
+    for (name, param) in &object.0 {
+        std::hash::Hash::hash(name, &mut hasher);
+        std::hash::Hash::hash(param, &mut hasher);
+    }
+    hasher.finish()
+}
+
 struct WriteContext<'pio, W: Write + Seek> {
     writer: W,
+    options: WriteOptions,
     list_count: u32,
     object_count: u32,
     param_count: u32,
@@ -103,6 +186,11 @@ struct WriteContext<'pio, W: Write + Seek> {
     offsets: FxHashMap<usize, u32>,
     string_offsets: FxHashMap<&'pio str, u32>,
     buffer_offsets: FxHashMap<u64, u32>,
+    /// Maps objects to the first identical object (in parameter write order)
+    /// when deduplicating objects.
+    duplicate_objects: FxHashMap<usize, usize>,
+    /// Offsets of the parameter entries written for each object.
+    param_array_offsets: FxHashMap<usize, u32>,
 }
 
 impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
@@ -128,11 +216,8 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_lists(&mut self, pio: &'pio ParameterIO) -> BinResult<()> {
-        fn write<W: Write + Seek>(
-            ctx: &mut WriteContext<W>,
-            list: &ParameterList,
-        ) -> BinResult<()> {
+    fn write_lists(&mut self, pio: &'pio ParameterIO) -> Result<()> {
+        fn write<W: Write + Seek>(ctx: &mut WriteContext<W>, list: &ParameterList) -> Result<()> {
             ctx.write_offset_for_parent(list, 0x4)?;
             for (name, list) in &list.lists.0 {
                 ctx.write_list(*name, list)?;
@@ -147,7 +232,7 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_objects(&mut self, list: &ParameterList) -> BinResult<()> {
+    fn write_objects(&mut self, list: &ParameterList) -> Result<()> {
         self.write_offset_for_parent(list, 0x8)?;
         for (name, object) in &list.objects.0 {
             self.write_object(*name, object)?;
@@ -159,13 +244,27 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
-    fn write_parameters(&mut self, list: &ParameterList) -> BinResult<()> {
+    fn write_parameters(&mut self, list: &ParameterList) -> Result<()> {
         for list in list.lists.0.values() {
             self.write_parameters(list)?;
         }
 
         for object in list.objects.0.values() {
+            let ptr = object as *const _ as usize;
+            if let Some(&original) = self.duplicate_objects.get(&ptr) {
+                let object_offset = self.get_offset(object);
+                let params_offset = self.param_array_offsets[&original];
+                self.write_at(
+                    object_offset + 0x4,
+                    rel_offset(object_offset, params_offset)?,
+                )?;
+                continue;
+            }
             self.write_offset_for_parent(object, 0x4)?;
+            if self.options.dedup_identical_objects {
+                let offset = self.writer.stream_position()? as u32;
+                self.param_array_offsets.insert(ptr, offset);
+            }
             for (name, param) in &object.0 {
                 self.write_parameter(*name, param)?;
             }
@@ -173,6 +272,32 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         Ok(())
     }
 
+    /// Find objects identical to one written earlier by `write_parameters`, so
+    /// that their parameters can be skipped.
+    fn find_duplicate_objects(
+        &mut self,
+        list: &'pio ParameterList,
+        seen: &mut FxHashMap<u64, Vec<&'pio ParameterObject>>,
+    ) {
+        for list in list.lists.0.values() {
+            self.find_duplicate_objects(list, seen);
+        }
+
+        for object in list.objects.0.values() {
+            if object.is_empty() {
+                continue;
+            }
+            let candidates = seen.entry(hash_object(object)).or_default();
+            match candidates.iter().find(|other| *other == &object) {
+                Some(original) => {
+                    self.duplicate_objects
+                        .insert(object as *const _ as usize, *original as *const _ as usize);
+                }
+                None => candidates.push(object),
+            }
+        }
+    }
+
     fn collect_parameters(&mut self, pio: &'pio ParameterIO) {
         // For some reason, the order in which parameter data is serialized is
         // not the order of parameter objects or even parameters... Rather, for
@@ -196,7 +321,12 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
 
             let mut process_one_object = || {
                 if let Some(obj) = object.borrow().as_ref() {
-                    for param in obj.0.values() {
+                    let is_duplicate = ctx
+                        .lock()
+                        .expect("Context should unlock")
+                        .duplicate_objects
+                        .contains_key(&(*obj as *const _ as usize));
+                    for param in obj.0.values().filter(|_| !is_duplicate) {
                         let mut ctx = ctx.lock().expect("Context should unlock");
                         if param.is_string_type() {
                             ctx.string_param_queue.push(param);
@@ -375,17 +505,135 @@ impl<'pio, W: Write + Seek> WriteContext<'pio, W> {
         &mut self,
         parent: &T,
         offset_in_parent: u32,
-    ) -> BinResult<()> {
+    ) -> Result<()> {
         let parent_offset = self.get_offset(parent);
-        let current_rel_offset = (self.writer.stream_position()? as u32 - parent_offset) / 4;
-        self.write_at(parent_offset + offset_in_parent, current_rel_offset as u16)?;
+        let current_offset = self.writer.stream_position()? as u32;
+        self.write_at(
+            parent_offset + offset_in_parent,
+            rel_offset(parent_offset, current_offset)?,
+        )?;
         Ok(())
     }
 }
 
+/// Offset of `target` relative to the entry at `entry`, as stored in list and
+/// object entries.
+fn rel_offset(entry: u32, target: u32) -> Result<u16> {
+    u16::try_from((target - entry) / 4).map_err(|_| {
+        crate::Error::InvalidData(
+            "Parameter IO too large: relative offset of a child does not fit in 16 bits",
+        )
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn alignment() {
+        let obj = ParameterObject::new()
+            .with_parameter("Bool", Parameter::Bool(true))
+            .with_parameter("F32", Parameter::F32(1.0))
+            .with_parameter(
+                "Vec3",
+                Parameter::Vec3(Vector3f {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                }),
+            )
+            .with_parameter(
+                "Quat",
+                Parameter::Quat(Quat {
+                    a: 1.0,
+                    b: 0.0,
+                    c: 0.0,
+                    d: 0.0,
+                }),
+            )
+            .with_parameter("Vec2", Parameter::Vec2(Vector2f { x: 1.0, y: 2.0 }))
+            .with_parameter(
+                "Vec4",
+                Parameter::Vec4(Vector4f {
+                    x: 1.0,
+                    y: 2.0,
+                    z: 3.0,
+                    t: 4.0,
+                }),
+            )
+            .with_parameter(
+                "Color",
+                Parameter::Color(Color {
+                    r: 0.5,
+                    g: 0.25,
+                    b: 0.125,
+                    a: 1.0,
+                }),
+            )
+            .with_parameter("Curve1", Parameter::Curve1(Default::default()))
+            .with_parameter("Curve2", Parameter::Curve2(Default::default()))
+            .with_parameter("Curve3", Parameter::Curve3(Default::default()))
+            .with_parameter("Curve4", Parameter::Curve4(Default::default()))
+            .with_parameter("I32", Parameter::I32(-1))
+            .with_parameter("String32", Parameter::String32("a".into()))
+            .with_parameter("String64", Parameter::String64(Box::new("ab".into())))
+            .with_parameter("String256", Parameter::String256(Box::new("abc".into())))
+            .with_parameter("StringRef", Parameter::StringRef("odd".into()))
+            .with_parameter("StringRef2", Parameter::StringRef("even".into()))
+            .with_parameter("BufferBinary", Parameter::BufferBinary(vec![1, 2, 3]))
+            .with_parameter("BufferF32", Parameter::BufferF32(vec![1.0]))
+            .with_parameter("BufferInt", Parameter::BufferInt(vec![1, -1]))
+            .with_parameter("BufferU32", Parameter::BufferU32(vec![1, 2, 3]))
+            .with_parameter("U32", Parameter::U32(1));
+        let i32_index = obj
+            .0
+            .keys()
+            .position(|name| *name == Name::from("I32"))
+            .unwrap();
+        for data_type in ["ab", "abc", "abcd"] {
+            let pio = ParameterIO::new()
+                .with_data_type(data_type)
+                .with_object("A", obj.clone())
+                .with_object("B", obj.clone());
+            let data = pio.to_binary();
+            let read_u16 = |offset: usize| u16::from_le_bytes([data[offset], data[offset + 1]]);
+            let mut parser =
+                super::super::parser::Parser::new(Cursor::new(&data), Default::default()).unwrap();
+            let root = parser.root_offset().unwrap();
+            assert_eq!(root % 4, 0);
+            let (_, _, objects) = parser.parse_list_header(root).unwrap();
+            assert_eq!(objects.len(), 2);
+            for obj_offset in objects {
+                let obj_offset = obj_offset as usize;
+                assert_eq!(obj_offset % 4, 0);
+                let params_offset = obj_offset + read_u16(obj_offset + 4) as usize * 4;
+                assert_eq!(params_offset % 4, 0);
+                let param_count = read_u16(obj_offset + 6) as usize;
+                assert_eq!(param_count, obj.len());
+                for i in 0..param_count {
+                    let offset = params_offset + 8 * i;
+                    assert_eq!(offset % 4, 0);
+                    let rel = u32::from_le_bytes([
+                        data[offset + 4],
+                        data[offset + 5],
+                        data[offset + 6],
+                        0,
+                    ]);
+                    let data_offset = params_offset + 8 * i + rel as usize * 4;
+                    assert_eq!(data_offset % 4, 0, "parameter {i} in {data_type}");
+                    assert!(data_offset < data.len(), "parameter {i} in {data_type}");
+                    // The offset must also point at the parameter's own data.
+                    if i == i32_index {
+                        let bytes = data[data_offset..data_offset + 4].try_into().unwrap();
+                        assert_eq!(i32::from_le_bytes(bytes), -1);
+                    }
+                }
+            }
+            assert_eq!(ParameterIO::from_binary(&data).unwrap(), pio);
+        }
+    }
+
     #[test]
     fn binary_roundtrip() {
         for file in jwalk::WalkDir::new("test/aamp")
@@ -400,9 +648,99 @@ mod tests {
             println!("{}", file.display());
             let data = std::fs::read(&file).unwrap();
             let pio = ParameterIO::from_binary(data).unwrap();
-            let new_bytes = pio.to_binary();
-            let new_pio = ParameterIO::from_binary(new_bytes).unwrap();
-            assert_eq!(pio, new_pio);
+            crate::assert_aamp_roundtrip!(pio);
+            let dedup_bytes = pio.to_binary_with_options(&WriteOptions {
+                dedup_identical_objects: true,
+                ..Default::default()
+            });
+            assert_eq!(pio, ParameterIO::from_binary(dedup_bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn compute_binary_size() {
+        for file in [
+            "test/aamp/Lizalfos.bphysics",
+            "test/aamp/DamageReactionTable.bxml",
+        ] {
+            let pio = ParameterIO::from_binary(std::fs::read(file).unwrap()).unwrap();
+            assert_eq!(pio.compute_binary_size(), pio.to_binary().len());
         }
+        assert_eq!(
+            ParameterIO::new().compute_binary_size(),
+            ParameterIO::new().to_binary().len()
+        );
+    }
+
+    #[test]
+    fn dedup_identical_objects() {
+        let object = ParameterObject::new()
+            .with_parameter("Speed", Parameter::F32(1.5))
+            .with_parameter("Count", Parameter::I32(3));
+        let pio = ParameterIO::new()
+            .with_object("A", object.clone())
+            .with_list(
+                "Actions",
+                ParameterList::new()
+                    .with_object("B", object.clone())
+                    .with_object("C", object),
+            );
+        let options = WriteOptions {
+            dedup_identical_objects: true,
+            ..Default::default()
+        };
+        let bytes = pio.to_binary();
+        let dedup_bytes = pio.to_binary_with_options(&options);
+        assert!(dedup_bytes.len() < bytes.len());
+        assert_eq!(ParameterIO::from_binary(dedup_bytes).unwrap(), pio);
+    }
+
+    #[test]
+    fn offset_overflow() {
+        // The filler's parameter entries put those of the next object more
+        // than 0xffff * 4 bytes after its object entry.
+        let filler = (0..40000)
+            .map(|i| (format!("Filler{i}"), Parameter::I32(0)))
+            .collect::<ParameterObject>();
+        let pio = ParameterIO::new()
+            .with_object("Filler", filler)
+            .with_object(
+                "Obj",
+                ParameterObject::new().with_parameter("A", Parameter::I32(1)),
+            );
+        assert!(matches!(
+            pio.write(Cursor::new(vec![])),
+            Err(crate::Error::InvalidData(_))
+        ));
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalize_strings() {
+        let pio = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter("Ref", Parameter::StringRef("Cafe\u{301}".into()))
+                .with_parameter("Fixed", Parameter::String32("Cafe\u{301}".into())),
+        );
+        let expected = ParameterIO::new().with_object(
+            "Obj",
+            ParameterObject::new()
+                .with_parameter("Ref", Parameter::StringRef("Caf\u{e9}".into()))
+                .with_parameter("Fixed", Parameter::String32("Caf\u{e9}".into())),
+        );
+        let bytes = pio.to_binary();
+        assert_eq!(ParameterIO::from_binary(&bytes).unwrap(), pio);
+        let parsed = ParameterIO::from_binary_with_options(&bytes, &crate::aamp::ParseOptions {
+            normalize_strings: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(parsed, expected);
+        let bytes = pio.to_binary_with_options(&WriteOptions {
+            normalize_strings: true,
+            ..Default::default()
+        });
+        assert_eq!(ParameterIO::from_binary(bytes).unwrap(), expected);
     }
 }
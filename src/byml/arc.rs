@@ -0,0 +1,114 @@
+use std::{ops::Deref, sync::Arc};
+
+use super::*;
+
+/// A reference-counted, copy-on-write BYML node, for sharing (parts of) a
+/// document between threads or data structures without deep clones.
+///
+/// Cloning an `ArcByml` only increments the reference count. Mutation goes
+/// through [`ArcByml::make_mut`], which clones the node first only if it is
+/// shared.
+///
+/// ```
+/// # use roead::byml::{ArcByml, Byml};
+/// let shared = Byml::I32(1).into_arc();
+/// let mut copy = shared.clone();
+/// *copy.make_mut() = Byml::I32(2);
+/// assert_eq!(*shared, Byml::I32(1));
+/// assert_eq!(*copy, Byml::I32(2));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArcByml(Arc<Byml>);
+
+impl ArcByml {
+    /// Wrap a node.
+    pub fn new(byml: Byml) -> Self {
+        Self(Arc::new(byml))
+    }
+
+    /// Get a mutable reference to the node, cloning it first if it is shared
+    /// with other `ArcByml`s.
+    pub fn make_mut(&mut self) -> &mut Byml {
+        Arc::make_mut(&mut self.0)
+    }
+
+    /// Unwrap the node, cloning it only if it is still shared.
+    pub fn into_inner(self) -> Byml {
+        Arc::try_unwrap(self.0).unwrap_or_else(|arc| (*arc).clone())
+    }
+
+    /// Returns true if both values point to the same node.
+    pub fn ptr_eq(&self, other: &ArcByml) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for ArcByml {
+    type Target = Byml;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<Byml> for ArcByml {
+    fn as_ref(&self) -> &Byml {
+        &self.0
+    }
+}
+
+impl From<Byml> for ArcByml {
+    fn from(byml: Byml) -> Self {
+        Self::new(byml)
+    }
+}
+
+impl From<Arc<Byml>> for ArcByml {
+    fn from(byml: Arc<Byml>) -> Self {
+        Self(byml)
+    }
+}
+
+impl From<ArcByml> for Arc<Byml> {
+    fn from(byml: ArcByml) -> Self {
+        byml.0
+    }
+}
+
+impl Byml {
+    /// Move the node into a shareable [`ArcByml`].
+    pub fn into_arc(self) -> ArcByml {
+        ArcByml::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copy_on_write() {
+        let byml: Byml = [("A", Byml::I32(1))].into_iter().collect();
+        let shared = byml.clone().into_arc();
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let shared = shared.clone();
+                std::thread::spawn(move || shared["A"].as_i32().unwrap())
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 1);
+        }
+
+        let mut copy = shared.clone();
+        assert!(copy.ptr_eq(&shared));
+        copy.make_mut()
+            .as_mut_hash()
+            .unwrap()
+            .insert("B".into(), Byml::Null);
+        assert!(!copy.ptr_eq(&shared));
+        assert_eq!(*shared, byml);
+        assert_eq!(copy.as_hash().unwrap().len(), 2);
+        assert_eq!(shared.into_inner(), byml);
+    }
+}
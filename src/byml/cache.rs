@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use super::*;
+pub use crate::util::CacheStats;
+use crate::util::ContentCache;
+
+/// Thread-safe cache of parsed BYML documents, keyed by a hash of their
+/// binary contents.
+///
+/// Like [`ParameterIOCache`](crate::aamp::ParameterIOCache), the cache only
+/// holds weak references, so a document is dropped as soon as the last
+/// [`Arc`] returned for it goes away. Entries are matched by a 64-bit content
+/// hash plus the data length.
+///
+/// ```
+/// # use roead::byml::*;
+/// let cache = BymlCache::new();
+/// let data = std::fs::read("test/byml/GameROMPlayer.byml")?;
+/// let first = cache.get_or_parse(&data)?;
+/// let second = cache.get_or_parse(&data)?;
+/// assert!(std::sync::Arc::ptr_eq(&first, &second));
+/// assert_eq!(cache.stats(), CacheStats {
+///     hits:   1,
+///     misses: 1,
+/// });
+/// # Ok::<(), roead::Error>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct BymlCache(ContentCache<Byml>);
+
+impl BymlCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Get the document for some binary data, parsing it only if the same
+    /// data is not already in the cache.
+    pub fn get_or_parse(&self, data: &[u8]) -> Result<Arc<Byml>> {
+        self.0.get_or_parse(data, |data| Byml::from_binary(data))
+    }
+
+    /// Hit and miss counts since the cache was created.
+    pub fn stats(&self) -> CacheStats {
+        self.0.stats()
+    }
+
+    /// The number of cached documents which are still alive.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns true if no cached documents are still alive.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all entries from the cache. The statistics are kept.
+    pub fn clear(&self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Endian;
+
+    #[test]
+    fn cache() {
+        let cache = Arc::new(BymlCache::new());
+        let byml: Byml = [("A", Byml::I32(1))].into_iter().collect();
+        let data = byml.to_binary(Endian::Little);
+        let first = cache.get_or_parse(&data).unwrap();
+        assert_eq!(*first, byml);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = cache.clone();
+                let data = data.clone();
+                std::thread::spawn(move || cache.get_or_parse(&data).unwrap())
+            })
+            .collect();
+        for thread in threads {
+            assert!(Arc::ptr_eq(&first, &thread.join().unwrap()));
+        }
+        assert_eq!(cache.stats(), CacheStats {
+            hits:   4,
+            misses: 1,
+        });
+
+        drop(first);
+        assert!(cache.is_empty());
+        cache.get_or_parse(&data).unwrap();
+        assert!(cache.get_or_parse(b"BY").is_err());
+        assert_eq!(cache.stats(), CacheStats {
+            hits:   4,
+            misses: 3,
+        });
+    }
+}
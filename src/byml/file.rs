@@ -0,0 +1,123 @@
+use std::path::Path;
+
+use super::*;
+
+/// File format of a BYML document, for [`Byml::to_file`].
+///
+/// Variants depend on the enabled features, so matches on this enum outside
+/// roead need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BymlFileFormat {
+    /// Binary BYML using the given options.
+    Binary(BymlWriteOptions),
+    /// YAML text.
+    ///
+    /// This is only available with the `yaml` feature.
+    #[cfg(feature = "yaml")]
+    Text,
+    /// Text if the file extension is `.yml` or `.yaml`, otherwise binary with
+    /// the default options.
+    Auto,
+}
+
+/// Guess the format of a BYML document from its first bytes. Data starting
+/// with a BYML magic (`BY` or `YB`) followed by a supported version in the
+/// matching byte order, or with a Yaz0 header, is binary; anything else is
+/// taken to be text. The version check keeps YAML which happens to start
+/// with `BY` or `YB` (e.g. `BYAML: 1`) from being read as binary.
+pub fn detect_format(data: &[u8]) -> BymlFileFormat {
+    let is_binary = match data {
+        [b'B', b'Y', v0, v1, ..] => is_valid_version(u16::from_be_bytes([*v0, *v1])),
+        [b'Y', b'B', v0, v1, ..] => is_valid_version(u16::from_le_bytes([*v0, *v1])),
+        _ => data.starts_with(b"Yaz0"),
+    };
+    if is_binary {
+        return BymlFileFormat::Binary(Default::default());
+    }
+    #[cfg(feature = "yaml")]
+    return BymlFileFormat::Text;
+    #[cfg(not(feature = "yaml"))]
+    BymlFileFormat::Binary(Default::default())
+}
+
+impl Byml {
+    /// Load a document from a file, which may be in binary or (with the
+    /// `yaml` feature) text format. See [`detect_format`].
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Byml> {
+        let data = std::fs::read(path)?;
+        match detect_format(&data) {
+            #[cfg(feature = "yaml")]
+            BymlFileFormat::Text => Byml::from_text(std::str::from_utf8(&data)?),
+            _ => Byml::from_binary(&data),
+        }
+    }
+
+    /// Save the document to a file in the given format.
+    pub fn to_file(&self, path: impl AsRef<Path>, format: BymlFileFormat) -> Result<()> {
+        let path = path.as_ref();
+        let data = match format {
+            BymlFileFormat::Binary(options) => self.to_binary_with_options(&options)?,
+            #[cfg(feature = "yaml")]
+            BymlFileFormat::Text => self.to_text()?.into_bytes(),
+            BymlFileFormat::Auto => {
+                let format = match path.extension().and_then(|ext| ext.to_str()) {
+                    #[cfg(feature = "yaml")]
+                    Some("yml" | "yaml") => BymlFileFormat::Text,
+                    _ => BymlFileFormat::Binary(Default::default()),
+                };
+                return self.to_file(path, format);
+            }
+        };
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files() {
+        let byml: Byml = [("A", Byml::I32(1)), ("B", Byml::String("C".into()))]
+            .into_iter()
+            .collect();
+        let dir = std::env::temp_dir().join(format!("roead-byml-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let binary = dir.join("test.byml");
+        byml.to_file(&binary, BymlFileFormat::Auto).unwrap();
+        let data = std::fs::read(&binary).unwrap();
+        assert_eq!(
+            detect_format(&data),
+            BymlFileFormat::Binary(Default::default())
+        );
+        assert_eq!(Byml::from_file(&binary).unwrap(), byml);
+
+        #[cfg(feature = "yaml")]
+        {
+            let text = dir.join("test.yml");
+            byml.to_file(&text, BymlFileFormat::Auto).unwrap();
+            let data = std::fs::read(&text).unwrap();
+            assert_eq!(detect_format(&data), BymlFileFormat::Text);
+            assert_eq!(Byml::from_file(&text).unwrap(), byml);
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(Byml::from_file(&binary).is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn detect() {
+        let binary = BymlFileFormat::Binary(Default::default());
+        assert_eq!(detect_format(b"YB\x02\x00"), binary);
+        assert_eq!(detect_format(b"BY\x00\x03"), binary);
+        assert_eq!(detect_format(b"Yaz0"), binary);
+        // Right magic, but the version is in the wrong byte order.
+        assert_eq!(detect_format(b"YB\x00\x02"), BymlFileFormat::Text);
+        assert_eq!(detect_format(b"BYAML: 1\n"), BymlFileFormat::Text);
+        assert_eq!(detect_format(b"YB"), BymlFileFormat::Text);
+    }
+}
@@ -0,0 +1,234 @@
+use serde_json::{json, Map, Value};
+
+use super::*;
+
+/// Key marking a JSON object as a tagged BYML node rather than a hash.
+const TYPE_KEY: &str = "__byml_type";
+
+fn tagged(type_: &str, value: Value) -> Value {
+    json!({ TYPE_KEY: type_, "value": value })
+}
+
+/// Non-finite floats have no JSON representation, so they are stored as
+/// strings instead.
+fn non_finite_to_json(value: f64) -> Value {
+    if value.is_nan() {
+        "nan".into()
+    } else if value.is_sign_positive() {
+        "inf".into()
+    } else {
+        "-inf".into()
+    }
+}
+
+fn float_from_json(value: &Value) -> Result<f64> {
+    match value {
+        Value::Number(num) => {
+            num.as_f64()
+                .ok_or(Error::InvalidData("Invalid float in JSON BYML node"))
+        }
+        Value::String(s) => {
+            match s.as_str() {
+                "nan" => Ok(f64::NAN),
+                "inf" => Ok(f64::INFINITY),
+                "-inf" => Ok(f64::NEG_INFINITY),
+                _ => Err(Error::InvalidDataD(format!("Invalid float: {s}"))),
+            }
+        }
+        _ => Err(Error::InvalidData("Invalid float in JSON BYML node")),
+    }
+}
+
+fn int_from_json<T: TryFrom<i128>>(value: &Value) -> Result<T> {
+    value
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| value.as_u64().map(i128::from))
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or(Error::InvalidData("Invalid integer in JSON BYML node"))
+}
+
+impl From<&Byml> for Value {
+    /// Convert a BYML node to JSON. Strings, bools, 32-bit ints, finite
+    /// floats, null, arrays and hashes are written as plain JSON values. Other
+    /// types are written as objects tagged with their type, e.g.
+    /// `{"__byml_type": "u32", "value": 42}`, so that the conversion back with
+    /// [`Byml::try_from`] is lossless. Binary data is written as an array of
+    /// bytes.
+    ///
+    /// This is only available with the `with-serde` feature.
+    fn from(byml: &Byml) -> Self {
+        match byml {
+            Byml::String(s) => s.as_str().into(),
+            Byml::BinaryData(data) => tagged("binary", data.as_slice().into()),
+            Byml::Array(array) => array.iter().map(Value::from).collect(),
+            Byml::Hash(hash) => {
+                let map: Map<std::string::String, Value> = hash
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.into()))
+                    .collect();
+                // A hash with the tag key of its own must be wrapped so that it
+                // is not taken for a tagged node.
+                if map.contains_key(TYPE_KEY) {
+                    tagged("hash", map.into())
+                } else {
+                    map.into()
+                }
+            }
+            Byml::Bool(b) => (*b).into(),
+            Byml::I32(i) => (*i).into(),
+            Byml::Float(f) => {
+                if f.is_finite() {
+                    // Go through the shortest representation so that the JSON
+                    // does not end up with f64 noise like 0.10000000149011612.
+                    f.to_string()
+                        .parse::<f64>()
+                        .map(Value::from)
+                        .unwrap_or(Value::Null)
+                } else {
+                    tagged("float", non_finite_to_json(*f as f64))
+                }
+            }
+            Byml::U32(u) => tagged("u32", (*u).into()),
+            Byml::I64(i) => tagged("i64", (*i).into()),
+            Byml::U64(u) => tagged("u64", (*u).into()),
+            Byml::Double(d) => {
+                tagged(
+                    "double",
+                    if d.is_finite() {
+                        (*d).into()
+                    } else {
+                        non_finite_to_json(*d)
+                    },
+                )
+            }
+            Byml::Null => Value::Null,
+        }
+    }
+}
+
+impl From<Byml> for Value {
+    fn from(byml: Byml) -> Self {
+        (&byml).into()
+    }
+}
+
+impl TryFrom<&Value> for Byml {
+    type Error = Error;
+
+    /// Convert JSON in the format produced by the `From<&Byml>` conversion
+    /// back to BYML. Untagged integers become [`Byml::I32`] and untagged
+    /// floats become [`Byml::Float`].
+    fn try_from(value: &Value) -> Result<Self> {
+        match value {
+            Value::Null => Ok(Byml::Null),
+            Value::Bool(b) => Ok(Byml::Bool(*b)),
+            Value::Number(num) => {
+                if num.is_f64() {
+                    Ok(Byml::Float(float_from_json(value)? as f32))
+                } else {
+                    int_from_json(value).map(Byml::I32)
+                }
+            }
+            Value::String(s) => Ok(Byml::String(s.as_str().into())),
+            Value::Array(array) => array.iter().map(Byml::try_from).collect(),
+            Value::Object(obj) => {
+                let Some(type_) = obj.get(TYPE_KEY) else {
+                    return obj
+                        .iter()
+                        .map(|(k, v)| Ok((k.as_str(), Byml::try_from(v)?)))
+                        .collect();
+                };
+                let value = obj.get("value").ok_or(Error::InvalidData(
+                    "Missing `value` in tagged JSON BYML node",
+                ))?;
+                match type_.as_str() {
+                    Some("binary") => {
+                        value
+                            .as_array()
+                            .ok_or(Error::InvalidData("Binary data must be a JSON array"))?
+                            .iter()
+                            .map(int_from_json)
+                            .collect::<Result<_>>()
+                            .map(Byml::BinaryData)
+                    }
+                    Some("hash") => {
+                        value
+                            .as_object()
+                            .ok_or(Error::InvalidData("Hash must be a JSON object"))?
+                            .iter()
+                            .map(|(k, v)| Ok((k.as_str(), Byml::try_from(v)?)))
+                            .collect()
+                    }
+                    Some("float") => Ok(Byml::Float(float_from_json(value)? as f32)),
+                    Some("u32") => int_from_json(value).map(Byml::U32),
+                    Some("i64") => int_from_json(value).map(Byml::I64),
+                    Some("u64") => int_from_json(value).map(Byml::U64),
+                    Some("double") => float_from_json(value).map(Byml::Double),
+                    _ => {
+                        Err(Error::InvalidDataD(format!(
+                            "Unknown BYML type in JSON: {type_}"
+                        )))
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl TryFrom<Value> for Byml {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        Byml::try_from(&value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_roundtrip() {
+        let byml: Byml = [
+            ("String", Byml::String("Hello".into())),
+            ("Binary", Byml::BinaryData(vec![0, 1, 255])),
+            ("Bool", Byml::Bool(true)),
+            ("I32", Byml::I32(-1)),
+            ("Float", Byml::Float(0.1)),
+            ("Inf", Byml::Float(f32::NEG_INFINITY)),
+            ("U32", Byml::U32(u32::MAX)),
+            ("I64", Byml::I64(i64::MIN)),
+            ("U64", Byml::U64(u64::MAX)),
+            ("Double", Byml::Double(std::f64::consts::PI)),
+            ("Null", Byml::Null),
+            (
+                "Array",
+                Byml::Array(vec![Byml::I32(1), Byml::String("2".into())]),
+            ),
+            (
+                "Tricky",
+                [
+                    (TYPE_KEY, Byml::String("u32".into())),
+                    ("value", Byml::I32(1)),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let value = Value::from(&byml);
+        assert_eq!(
+            value["U32"],
+            json!({ "__byml_type": "u32", "value": u32::MAX })
+        );
+        assert_eq!(value["Float"], json!(0.1));
+        let text = serde_json::to_string(&value).unwrap();
+        let parsed = Byml::try_from(serde_json::from_str::<Value>(&text).unwrap()).unwrap();
+        assert_eq!(parsed, byml);
+
+        assert!(Byml::try_from(json!({ "__byml_type": "u32", "value": -1 })).is_err());
+        assert!(Byml::try_from(json!({ "__byml_type": "what", "value": 1 })).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use super::*;
+
+/// A conflicting change found by [`three_way_merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BymlMergeConflict {
+    /// Path to the conflicting node, made of hash keys and array indices
+    /// (relative to the base document) separated by `/`. The root node has an
+    /// empty path.
+    pub path:   String,
+    /// Our version of the node, or `None` if we removed it. For conflicting
+    /// array changes this is an array with our version of the affected range.
+    pub ours:   Option<Byml>,
+    /// Their version of the node, or `None` if they removed it. For
+    /// conflicting array changes this is an array with their version of the
+    /// affected range.
+    pub theirs: Option<Byml>,
+}
+
+/// The result of a [`three_way_merge`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThreeWayBymlMergeResult {
+    /// The merged document. Conflicting changes are resolved in favour of
+    /// `ours`.
+    pub merged: Byml,
+    /// All conflicting changes.
+    pub conflicts: Vec<BymlMergeConflict>,
+}
+
+impl ThreeWayBymlMergeResult {
+    /// Returns `true` if the merge produced no conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merge two sets of changes (`ours` and `theirs`) to a common `base`
+/// document.
+///
+/// Nodes changed on only one side relative to `base` are taken from that side.
+/// Hash nodes changed on both sides are merged key by key, so additions from
+/// either side are kept. Array nodes changed on both sides are diffed against
+/// the base array (using a longest common subsequence), and changes to
+/// different elements are combined; where both sides changed the same
+/// elements, those elements are merged recursively if neither side changed the
+/// length of the affected range. Anything else changed differently on both
+/// sides is reported as a conflict.
+pub fn three_way_merge(base: &Byml, ours: &Byml, theirs: &Byml) -> ThreeWayBymlMergeResult {
+    let mut conflicts = vec![];
+    let merged =
+        merge_nodes(Some(base), Some(ours), Some(theirs), "", &mut conflicts).unwrap_or(Byml::Null);
+    ThreeWayBymlMergeResult { merged, conflicts }
+}
+
+fn join_path(path: &str, segment: impl std::fmt::Display) -> String {
+    if path.is_empty() {
+        format!("{segment}").into()
+    } else {
+        format!("{path}/{segment}").into()
+    }
+}
+
+fn merge_nodes(
+    base: Option<&Byml>,
+    ours: Option<&Byml>,
+    theirs: Option<&Byml>,
+    path: &str,
+    conflicts: &mut Vec<BymlMergeConflict>,
+) -> Option<Byml> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    } else if ours == base {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        (None | Some(Byml::Hash(_)), Some(Byml::Hash(o)), Some(Byml::Hash(t))) => {
+            let empty = Hash::default();
+            let b = base.and_then(|b| b.as_hash().ok()).unwrap_or(&empty);
+            Some(Byml::Hash(merge_hashes(b, o, t, path, conflicts)))
+        }
+        (None | Some(Byml::Array(_)), Some(Byml::Array(o)), Some(Byml::Array(t))) => {
+            let b = base.and_then(|b| b.as_array().ok()).unwrap_or(&[]);
+            Some(Byml::Array(merge_arrays(b, o, t, path, conflicts)))
+        }
+        _ => {
+            conflicts.push(BymlMergeConflict {
+                path:   path.into(),
+                ours:   ours.cloned(),
+                theirs: theirs.cloned(),
+            });
+            ours.cloned()
+        }
+    }
+}
+
+fn merge_hashes(
+    base: &Hash,
+    ours: &Hash,
+    theirs: &Hash,
+    path: &str,
+    conflicts: &mut Vec<BymlMergeConflict>,
+) -> Hash {
+    let keys = ours
+        .keys()
+        .chain(theirs.keys().filter(|k| !ours.contains_key(*k)));
+    let mut merged = Hash::default();
+    for key in keys {
+        if let Some(value) = merge_nodes(
+            base.get(key),
+            ours.get(key),
+            theirs.get(key),
+            &join_path(path, key),
+            conflicts,
+        ) {
+            merged.insert(key.clone(), value);
+        }
+    }
+    merged
+}
+
+/// A change replacing `base[start..end]` with `replacement`.
+#[derive(Debug)]
+struct Hunk<'a> {
+    start: usize,
+    end: usize,
+    replacement: &'a [Byml],
+}
+
+fn hunks<'a>(base: &[Byml], other: &'a [Byml]) -> Vec<Hunk<'a>> {
+    let mut hunks = vec![];
+    let (mut b, mut o) = (0, 0);
+    for (bi, oi) in crate::util::common_subsequence(base, other)
+        .into_iter()
+        .chain(std::iter::once((base.len(), other.len())))
+    {
+        if bi > b || oi > o {
+            hunks.push(Hunk {
+                start: b,
+                end: bi,
+                replacement: &other[o..oi],
+            });
+        }
+        b = bi + 1;
+        o = oi + 1;
+    }
+    hunks
+}
+
+/// Apply the hunks (which must lie in `base[start..end]`) to that range.
+fn apply_hunks(base: &[Byml], hunks: &[&Hunk], start: usize, end: usize) -> Vec<Byml> {
+    let mut result = vec![];
+    let mut pos = start;
+    for hunk in hunks {
+        result.extend_from_slice(&base[pos..hunk.start]);
+        result.extend_from_slice(hunk.replacement);
+        pos = hunk.end;
+    }
+    result.extend_from_slice(&base[pos..end]);
+    result
+}
+
+fn merge_arrays(
+    base: &[Byml],
+    ours: &[Byml],
+    theirs: &[Byml],
+    path: &str,
+    conflicts: &mut Vec<BymlMergeConflict>,
+) -> Vec<Byml> {
+    let our_hunks = hunks(base, ours);
+    let their_hunks = hunks(base, theirs);
+    let (mut i, mut j) = (0, 0);
+    let mut merged = Vec::with_capacity(ours.len().max(theirs.len()));
+    let mut pos = 0;
+    let sort_key = |h: &Hunk| (h.start, h.end);
+    while i < our_hunks.len() || j < their_hunks.len() {
+        // Start a region with whichever hunk comes first, then grow it until
+        // no hunk from either side overlaps it.
+        let first = match (our_hunks.get(i), their_hunks.get(j)) {
+            (Some(o), Some(t)) => sort_key(o) <= sort_key(t),
+            (o, _) => o.is_some(),
+        };
+        let (start, mut end) = if first {
+            (our_hunks[i].start, our_hunks[i].end)
+        } else {
+            (their_hunks[j].start, their_hunks[j].end)
+        };
+        let overlaps = |h: &Hunk, end: usize| {
+            h.start < end || (h.start == h.end && start == end && h.start == start)
+        };
+        let (oi, tj) = (i, j);
+        loop {
+            if let Some(h) = our_hunks.get(i) && overlaps(h, end) {
+                end = end.max(h.end);
+                i += 1;
+            } else if let Some(h) = their_hunks.get(j) && overlaps(h, end) {
+                end = end.max(h.end);
+                j += 1;
+            } else {
+                break;
+            }
+        }
+        merged.extend_from_slice(&base[pos..start]);
+        pos = end;
+        let our_region: Vec<&Hunk> = our_hunks[oi..i].iter().collect();
+        let their_region: Vec<&Hunk> = their_hunks[tj..j].iter().collect();
+        if our_region.is_empty() || their_region.is_empty() {
+            merged.extend(apply_hunks(
+                base,
+                if our_region.is_empty() {
+                    &their_region
+                } else {
+                    &our_region
+                },
+                start,
+                end,
+            ));
+            continue;
+        }
+        let our_version = apply_hunks(base, &our_region, start, end);
+        let their_version = apply_hunks(base, &their_region, start, end);
+        if our_version == their_version {
+            merged.extend(our_version);
+        } else if our_version.len() == end - start && their_version.len() == end - start {
+            for (k, (o, t)) in our_version.iter().zip(their_version.iter()).enumerate() {
+                let index = start + k;
+                merged.extend(merge_nodes(
+                    Some(&base[index]),
+                    Some(o),
+                    Some(t),
+                    &join_path(path, index),
+                    conflicts,
+                ));
+            }
+        } else {
+            conflicts.push(BymlMergeConflict {
+                path:   join_path(path, start),
+                ours:   Some(Byml::Array(our_version.clone())),
+                theirs: Some(Byml::Array(their_version)),
+            });
+            merged.extend(our_version);
+        }
+    }
+    merged.extend_from_slice(&base[pos..]);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash<const N: usize>(entries: [(&str, Byml); N]) -> Byml {
+        Byml::Hash(entries.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
+
+    fn ints<const N: usize>(values: [i32; N]) -> Byml {
+        Byml::Array(values.into_iter().map(Byml::I32).collect())
+    }
+
+    #[test]
+    fn merge_hashes() {
+        let base = hash([("A", Byml::I32(1)), ("B", Byml::I32(1))]);
+        let ours = hash([
+            ("A", Byml::I32(2)),
+            ("B", Byml::I32(1)),
+            ("C", Byml::I32(3)),
+        ]);
+        let theirs = hash([
+            ("A", Byml::I32(1)),
+            ("B", Byml::I32(4)),
+            ("D", Byml::I32(5)),
+        ]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(
+            result.merged,
+            hash([
+                ("A", Byml::I32(2)),
+                ("B", Byml::I32(4)),
+                ("C", Byml::I32(3)),
+                ("D", Byml::I32(5))
+            ])
+        );
+
+        let theirs = hash([("A", Byml::I32(6)), ("B", Byml::I32(1))]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts, vec![BymlMergeConflict {
+            path:   "A".into(),
+            ours:   Some(Byml::I32(2)),
+            theirs: Some(Byml::I32(6)),
+        }]);
+        assert_eq!(result.merged["A"], Byml::I32(2));
+    }
+
+    #[test]
+    fn merge_arrays() {
+        let base = ints([1, 2, 3, 4, 5]);
+        let ours = ints([0, 1, 2, 3, 5]);
+        let theirs = ints([1, 2, 10, 3, 4, 5, 6]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(result.merged, ints([0, 1, 2, 10, 3, 5, 6]));
+
+        let ours = ints([1, 7, 3, 4, 5]);
+        let theirs = ints([1, 8, 9, 3, 4, 5]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts.len(), 1);
+        assert_eq!(result.conflicts[0].path, "1");
+        assert_eq!(result.merged, ours);
+    }
+
+    #[test]
+    fn merge_nested() {
+        let actor = |name: &str, hp: i32, scale: f32| {
+            hash([
+                ("name", Byml::String(name.into())),
+                ("hp", Byml::I32(hp)),
+                ("scale", Byml::Float(scale)),
+            ])
+        };
+        let doc = |actors: Vec<Byml>| hash([("Actors", Byml::Array(actors))]);
+        let base = doc(vec![actor("A", 1, 1.0), actor("B", 1, 1.0)]);
+        let ours = doc(vec![actor("A", 5, 1.0), actor("B", 1, 1.0)]);
+        let theirs = doc(vec![
+            actor("A", 1, 2.0),
+            actor("B", 1, 1.0),
+            actor("C", 1, 1.0),
+        ]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert!(result.is_clean());
+        assert_eq!(
+            result.merged,
+            doc(vec![
+                actor("A", 5, 2.0),
+                actor("B", 1, 1.0),
+                actor("C", 1, 1.0)
+            ])
+        );
+
+        let theirs = doc(vec![actor("A", 7, 1.0), actor("B", 1, 1.0)]);
+        let result = three_way_merge(&base, &ours, &theirs);
+        assert_eq!(result.conflicts[0].path, "Actors/0/hp");
+    }
+}
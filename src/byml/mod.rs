@@ -42,7 +42,8 @@
 //! ```
 //!
 //! Most of the node types are fairly self-explanatory. Arrays are implemented
-//! as `Vec<Byml>`, and hash nodes as `FxHashMap<String, Byml>`.
+//! as `Vec<Byml>`, and hash nodes as `FxHashMap<String, Byml>`, which does not
+//! preserve insertion order (see [`Hash`]).
 //!
 //! For convenience, a `Byml` *known* to be an array or hash node can be
 //! indexed. **Panics if the node has the wrong type, the index has the wrong
@@ -57,6 +58,12 @@
 //! # Ok(())
 //! # }
 //! ```
+mod arc;
+mod cache;
+mod file;
+#[cfg(feature = "with-serde")]
+mod json;
+mod merge;
 #[cfg(feature = "yaml")]
 mod text;
 mod writer;
@@ -65,6 +72,14 @@ use smartstring::alias::String;
 
 use crate::{Error, Result};
 mod parser;
+pub use arc::ArcByml;
+pub use cache::{BymlCache, CacheStats};
+pub use file::{detect_format, BymlFileFormat};
+pub use merge::{three_way_merge, BymlMergeConflict, ThreeWayBymlMergeResult};
+pub use parser::{BymlParseOptions, BymlParseStats, BymlWarning, ParseResult, YamlVersion};
+#[cfg(feature = "yaml")]
+pub use text::{BymlTextOptions, IntFormat};
+pub use writer::{BymlWriteOptions, Platform};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[binrw::binrw]
@@ -114,7 +129,24 @@ pub enum BymlError {
     ParseError(&'static str),
 }
 
+impl From<BymlError> for Error {
+    fn from(err: BymlError) -> Self {
+        match err {
+            BymlError::BinaryRwError(err) => Error::BinarySerde(err),
+            BymlError::IoError(err) => Error::Io(err),
+            BymlError::ParseError(msg) => Error::InvalidData(msg),
+            err => Error::InvalidDataD(err.to_string()),
+        }
+    }
+}
+
 /// A BYML hash node.
+///
+/// Iteration order is unspecified and does not reflect insertion order or the
+/// order of the source document. Since the BYML format requires hash entries to
+/// be sorted by key, both binary and text output always write entries in
+/// sorted key order, so serializing equal hashes always gives identical
+/// output.
 pub type Hash = rustc_hash::FxHashMap<String, Byml>;
 
 /// Convenience type used for indexing into `Byml`s
@@ -173,7 +205,33 @@ pub enum Byml {
     Null,
 }
 
+macro_rules! is_variant {
+    ($($fn:ident => $variant:ident, $desc:literal;)*) => {
+        $(
+            #[doc = concat!("Checks if the BYML node is ", $desc, ".")]
+            #[inline(always)]
+            pub fn $fn(&self) -> bool {
+                matches!(self, Self::$variant(_))
+            }
+        )*
+    };
+}
+
 impl Byml {
+    is_variant! {
+        is_hash => Hash, "a hash";
+        is_array => Array, "an array";
+        is_string => String, "a string";
+        is_bool => Bool, "a bool";
+        is_i32 => I32, "an i32";
+        is_u32 => U32, "a u32";
+        is_i64 => I64, "an i64";
+        is_u64 => U64, "a u64";
+        is_float => Float, "a float";
+        is_double => Double, "a double";
+        is_binary_data => BinaryData, "binary data";
+    }
+
     fn type_name(&self) -> String {
         match self {
             Byml::String(_) => "String".into(),
@@ -192,10 +250,32 @@ impl Byml {
     }
 
     /// Checks if the BYML node is a null node
+    #[inline(always)]
     pub fn is_null(&self) -> bool {
         matches!(self, Self::Null)
     }
 
+    /// Checks if the BYML node is a container (a hash or an array).
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let array = Byml::Array(vec![Byml::I32(1)]);
+    /// assert!(array.is_array() && array.is_container());
+    /// assert!(array[0].is_i32() && array[0].is_scalar());
+    /// assert!(!array[0].is_u32());
+    /// ```
+    #[inline(always)]
+    pub fn is_container(&self) -> bool {
+        matches!(self, Self::Hash(_) | Self::Array(_))
+    }
+
+    /// Checks if the BYML node is a scalar, i.e. anything but a hash or an
+    /// array. Null and binary data count as scalars.
+    #[inline(always)]
+    pub fn is_scalar(&self) -> bool {
+        !self.is_container()
+    }
+
     /// Get a reference to the inner bool value.
     pub fn as_bool(&self) -> Result<bool> {
         if let Self::Bool(v) = self {
@@ -536,6 +616,328 @@ impl Byml {
             Err(Error::TypeError(self.type_name(), "Hash"))
         }
     }
+
+    /// Get the value of a key in a hash node, converted to the requested
+    /// type. Fails if this is not a hash node, if the key is missing, or if
+    /// the value has a different type.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let byml = Byml::from_iter([("HP", Byml::I32(40))]);
+    /// let hp: i32 = byml.get_typed("HP")?;
+    /// assert_eq!(hp, 40);
+    /// assert!(byml.get_typed::<f32>("HP").is_err());
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn get_typed<'a, T>(&'a self, key: &str) -> Result<T>
+    where
+        T: TryFrom<&'a Byml, Error = Error>,
+    {
+        self.as_hash()?
+            .get(key)
+            .ok_or_else(|| Error::InvalidDataD(format!("BYML hash missing key {key}")))?
+            .try_into()
+    }
+
+    /// Get the value of a key in a hash node, converted to the requested
+    /// type, or `default` if this is not a hash node, if the key is missing,
+    /// or if the value has a different type.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let byml = Byml::from_iter([
+    ///     ("HP", Byml::I32(40)),
+    ///     ("Name", Byml::String("Bokoblin".into())),
+    /// ]);
+    /// assert_eq!(byml.get_typed_or("HP", 1), 40);
+    /// assert_eq!(byml.get_i32_or("Attack", 5), 5);
+    /// assert_eq!(byml.get_f32_or("HP", 1.0), 1.0);
+    /// assert_eq!(byml.get_str_or("Name", ""), "Bokoblin");
+    /// assert!(Byml::Null.get_bool_or("HP", true));
+    /// ```
+    pub fn get_typed_or<'a, T>(&'a self, key: &str, default: T) -> T
+    where
+        T: TryFrom<&'a Byml, Error = Error>,
+    {
+        self.as_hash()
+            .ok()
+            .and_then(|hash| hash.get(key))
+            .and_then(|value| T::try_from(value).ok())
+            .unwrap_or(default)
+    }
+
+    /// Shorthand for [`Byml::get_typed_or`] with a bool.
+    pub fn get_bool_or(&self, key: &str, default: bool) -> bool {
+        self.get_typed_or(key, default)
+    }
+
+    /// Shorthand for [`Byml::get_typed_or`] with an i32.
+    pub fn get_i32_or(&self, key: &str, default: i32) -> i32 {
+        self.get_typed_or(key, default)
+    }
+
+    /// Shorthand for [`Byml::get_typed_or`] with a u32.
+    pub fn get_u32_or(&self, key: &str, default: u32) -> u32 {
+        self.get_typed_or(key, default)
+    }
+
+    /// Shorthand for [`Byml::get_typed_or`] with an f32.
+    pub fn get_f32_or(&self, key: &str, default: f32) -> f32 {
+        self.get_typed_or(key, default)
+    }
+
+    /// Shorthand for [`Byml::get_typed_or`] with a string.
+    pub fn get_str_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.get_typed_or(key, default)
+    }
+
+    /// Get the node at `path`, which is made up of hash keys and array
+    /// indices separated by `/`, e.g. `Actors/12/name`. An empty path refers
+    /// to this node. Hash keys which contain a `/` cannot be reached this
+    /// way. Returns `None` if anything on the path is missing.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let byml = Byml::from_iter([(
+    ///     "Actors",
+    ///     Byml::Array(vec![Byml::from_iter([(
+    ///         "name",
+    ///         Byml::String("Enemy_Lizalfos".into()),
+    ///     )])]),
+    /// )]);
+    /// assert_eq!(
+    ///     byml.get_path("Actors/0/name"),
+    ///     Some(&Byml::String("Enemy_Lizalfos".into()))
+    /// );
+    /// assert_eq!(byml.get_path("Actors/1/name"), None);
+    /// assert_eq!(byml.get_path("Actors/name"), None);
+    /// let actor = byml.clone_subtree("Actors/0").unwrap();
+    /// assert_eq!(actor, byml["Actors"][0]);
+    /// ```
+    pub fn get_path(&self, path: &str) -> Option<&Byml> {
+        path.split('/')
+            .filter(|part| !part.is_empty())
+            .try_fold(self, |node, part| {
+                match node {
+                    Byml::Hash(hash) => hash.get(part),
+                    Byml::Array(array) => array.get(part.parse::<usize>().ok()?),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Get a deep copy of the node at `path` (see [`Byml::get_path`]). Only
+    /// that node is cloned, not the rest of the document.
+    pub fn clone_subtree(&self, path: &str) -> Option<Byml> {
+        self.get_path(path).cloned()
+    }
+
+    /// Get the entry for a key in a hash node, for in-place insertion or
+    /// modification. Fails if this is not a hash node.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let mut byml = Byml::from_iter([("HP", Byml::I32(40))]);
+    /// byml.entry_hash("HP")?.or_insert(Byml::I32(1));
+    /// byml.entry_hash("Attack")?.or_insert(Byml::I32(5));
+    /// assert_eq!(byml["HP"], Byml::I32(40));
+    /// assert_eq!(byml["Attack"], Byml::I32(5));
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn entry_hash(
+        &mut self,
+        key: impl Into<String>,
+    ) -> Result<std::collections::hash_map::Entry<'_, String, Byml>> {
+        Ok(self.as_mut_hash()?.entry(key.into()))
+    }
+
+    /// Iterate over the nodes of an array node. Returns `None` if this is not
+    /// an array node.
+    pub fn iter_array(&self) -> Option<std::slice::Iter<'_, Byml>> {
+        self.as_array().ok().map(|array| array.iter())
+    }
+
+    /// Mutably iterate over the nodes of an array node. Returns `None` if this
+    /// is not an array node.
+    pub fn iter_array_mut(&mut self) -> Option<std::slice::IterMut<'_, Byml>> {
+        self.as_mut_array().ok().map(|array| array.iter_mut())
+    }
+
+    /// Iterate over the key-value pairs of a hash node. Returns `None` if this
+    /// is not a hash node.
+    pub fn iter_hash(&self) -> Option<std::collections::hash_map::Iter<'_, String, Byml>> {
+        self.as_hash().ok().map(|hash| hash.iter())
+    }
+
+    /// Mutably iterate over the key-value pairs of a hash node. Returns `None`
+    /// if this is not a hash node.
+    pub fn iter_hash_mut(
+        &mut self,
+    ) -> Option<std::collections::hash_map::IterMut<'_, String, Byml>> {
+        self.as_mut_hash().ok().map(|hash| hash.iter_mut())
+    }
+
+    /// Iterate over the values of a hash node. Returns `None` if this is not a
+    /// hash node.
+    pub fn iter_hash_values(&self) -> Option<impl Iterator<Item = &Byml>> {
+        self.as_hash().ok().map(|hash| hash.values())
+    }
+
+    /// Iterate over the key-value pairs of a hash node in sorted key order,
+    /// which is also the order used by binary and text output. Returns `None`
+    /// if this is not a hash node.
+    ///
+    /// [`Hash`] itself is unordered, so this collects and sorts the entries
+    /// first. Use [`Byml::iter_hash`] if order does not matter.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let byml = Byml::from_iter([("B", Byml::I32(2)), ("A", Byml::I32(1))]);
+    /// let keys: Vec<_> = byml
+    ///     .iter_hash_sorted()
+    ///     .unwrap()
+    ///     .map(|(k, _)| k.as_str())
+    ///     .collect();
+    /// assert_eq!(keys, ["A", "B"]);
+    /// ```
+    pub fn iter_hash_sorted(&self) -> Option<impl Iterator<Item = (&String, &Byml)>> {
+        self.as_hash().ok().map(|hash| {
+            let mut entries: Vec<_> = hash.iter().collect();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+            entries.into_iter()
+        })
+    }
+
+    /// Compare two documents, treating floats and doubles as equal if they
+    /// differ by at most `epsilon`. Infinities of the same sign, and NaNs,
+    /// compare equal to each other. Other node types are compared exactly.
+    pub fn approx_eq(&self, other: &Byml, epsilon: f64) -> bool {
+        let float_eq =
+            |a: f64, b: f64| a == b || (a - b).abs() <= epsilon || (a.is_nan() && b.is_nan());
+        match (self, other) {
+            (Byml::Float(a), Byml::Float(b)) => float_eq(*a as f64, *b as f64),
+            (Byml::Double(a), Byml::Double(b)) => float_eq(*a, *b),
+            (Byml::Array(a), Byml::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Byml::Hash(a), Byml::Hash(b)) => {
+                a.len() == b.len()
+                    && a.iter().all(|(key, value)| {
+                        b.get(key)
+                            .map_or(false, |other| value.approx_eq(other, epsilon))
+                    })
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Sort the nodes of an array node. Nodes are ordered by type first, in
+    /// the order of the [`Byml`] variants, and then by value: strings and
+    /// binary data lexicographically, bools and integers by value, floats and
+    /// doubles by [`f64::total_cmp`], arrays element by element, and hashes by
+    /// their entries in key order. The sort is stable.
+    pub fn sort_array(&mut self) -> Result<()> {
+        self.as_mut_array()?.sort_by(total_cmp);
+        Ok(())
+    }
+
+    /// Append a node to an array node. Fails if this is not an array node.
+    ///
+    /// ```
+    /// # use roead::byml::Byml;
+    /// let mut byml = Byml::Array(vec![]);
+    /// byml.push(Byml::I32(1))?;
+    /// byml.insert_at(0, Byml::I32(0))?;
+    /// assert_eq!(byml, Byml::Array(vec![Byml::I32(0), Byml::I32(1)]));
+    /// assert_eq!(byml.pop()?, Some(Byml::I32(1)));
+    /// assert_eq!(byml.remove_at(0)?, Byml::I32(0));
+    /// assert!(byml.remove_at(0).is_err());
+    /// assert!(Byml::Null.push(Byml::I32(1)).is_err());
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn push(&mut self, value: Byml) -> Result<()> {
+        self.as_mut_array()?.push(value);
+        Ok(())
+    }
+
+    /// Remove the last node of an array node, returning `None` if the array
+    /// is empty. Fails if this is not an array node.
+    pub fn pop(&mut self) -> Result<Option<Byml>> {
+        Ok(self.as_mut_array()?.pop())
+    }
+
+    /// Insert a node into an array node at `index`, shifting all following
+    /// nodes. Fails if this is not an array node or if `index` is greater
+    /// than its length.
+    pub fn insert_at(&mut self, index: usize, value: Byml) -> Result<()> {
+        let array = self.as_mut_array()?;
+        if index > array.len() {
+            return Err(Error::InvalidDataD(format!(
+                "Insertion index {index} out of range for array of length {}",
+                array.len()
+            )));
+        }
+        array.insert(index, value);
+        Ok(())
+    }
+
+    /// Remove and return the node at `index` of an array node, shifting all
+    /// following nodes. Fails if this is not an array node or if `index` is
+    /// out of range.
+    pub fn remove_at(&mut self, index: usize) -> Result<Byml> {
+        let array = self.as_mut_array()?;
+        if index >= array.len() {
+            return Err(Error::InvalidDataD(format!(
+                "Index {index} out of range for array of length {}",
+                array.len()
+            )));
+        }
+        Ok(array.remove(index))
+    }
+}
+
+macro_rules! impl_try_from_ref {
+    ($($type:ty => $accessor:ident),* $(,)?) => {
+        $(
+            impl<'a> TryFrom<&'a Byml> for $type {
+                type Error = Error;
+
+                fn try_from(value: &'a Byml) -> Result<Self> {
+                    value.$accessor().map(Into::into)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_ref!(
+    bool => as_bool,
+    i32 => as_i32,
+    u32 => as_u32,
+    i64 => as_i64,
+    u64 => as_u64,
+    f32 => as_float,
+    f64 => as_double,
+    &'a String => as_string,
+    &'a [Byml] => as_array,
+    Vec<Byml> => as_array,
+    &'a Hash => as_hash,
+);
+
+impl<'a> TryFrom<&'a Byml> for &'a str {
+    type Error = Error;
+
+    fn try_from(value: &'a Byml) -> Result<Self> {
+        value.as_string().map(|s| s.as_str())
+    }
+}
+
+impl<'a> TryFrom<&'a Byml> for String {
+    type Error = Error;
+
+    fn try_from(value: &'a Byml) -> Result<Self> {
+        value.as_string().cloned()
+    }
 }
 
 impl From<bool> for Byml {
@@ -781,6 +1183,80 @@ impl PartialEq for Byml {
     }
 }
 
+/// Total order of nodes used by [`Byml::sort_array`].
+fn total_cmp(a: &Byml, b: &Byml) -> std::cmp::Ordering {
+    fn rank(byml: &Byml) -> u8 {
+        match byml {
+            Byml::String(_) => 0,
+            Byml::BinaryData(_) => 1,
+            Byml::Array(_) => 2,
+            Byml::Hash(_) => 3,
+            Byml::Bool(_) => 4,
+            Byml::I32(_) => 5,
+            Byml::Float(_) => 6,
+            Byml::U32(_) => 7,
+            Byml::I64(_) => 8,
+            Byml::U64(_) => 9,
+            Byml::Double(_) => 10,
+            Byml::Null => 11,
+        }
+    }
+    match (a, b) {
+        (Byml::String(a), Byml::String(b)) => a.cmp(b),
+        (Byml::BinaryData(a), Byml::BinaryData(b)) => a.cmp(b),
+        (Byml::Array(a), Byml::Array(b)) => {
+            a.iter()
+                .zip(b)
+                .map(|(a, b)| total_cmp(a, b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len()))
+        }
+        (Byml::Hash(a), Byml::Hash(b)) => {
+            fn sorted(hash: &Hash) -> Vec<(&String, &Byml)> {
+                let mut entries: Vec<_> = hash.iter().collect();
+                entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+                entries
+            }
+            let (a, b) = (sorted(a), sorted(b));
+            a.iter()
+                .zip(&b)
+                .map(|((k1, v1), (k2, v2))| k1.cmp(k2).then_with(|| total_cmp(v1, v2)))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len()))
+        }
+        (Byml::Bool(a), Byml::Bool(b)) => a.cmp(b),
+        (Byml::I32(a), Byml::I32(b)) => a.cmp(b),
+        (Byml::Float(a), Byml::Float(b)) => a.total_cmp(b),
+        (Byml::U32(a), Byml::U32(b)) => a.cmp(b),
+        (Byml::I64(a), Byml::I64(b)) => a.cmp(b),
+        (Byml::U64(a), Byml::U64(b)) => a.cmp(b),
+        (Byml::Double(a), Byml::Double(b)) => a.total_cmp(b),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+impl PartialOrd for Byml {
+    /// Compares scalar nodes of the same type: strings lexicographically, and
+    /// bools and numbers by value. Nodes of different types and unequal
+    /// container or binary nodes are not comparable.
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self == other {
+            return Some(std::cmp::Ordering::Equal);
+        }
+        match (self, other) {
+            (Byml::String(s1), Byml::String(s2)) => s1.partial_cmp(s2),
+            (Byml::Bool(b1), Byml::Bool(b2)) => b1.partial_cmp(b2),
+            (Byml::I32(i1), Byml::I32(i2)) => i1.partial_cmp(i2),
+            (Byml::Float(f1), Byml::Float(f2)) => f1.partial_cmp(f2),
+            (Byml::U32(u1), Byml::U32(u2)) => u1.partial_cmp(u2),
+            (Byml::I64(i1), Byml::I64(i2)) => i1.partial_cmp(i2),
+            (Byml::U64(u1), Byml::U64(u2)) => u1.partial_cmp(u2),
+            (Byml::Double(d1), Byml::Double(d2)) => d1.partial_cmp(d2),
+            _ => None,
+        }
+    }
+}
+
 impl PartialEq<Byml> for &Byml {
     fn eq(&self, other: &Byml) -> bool {
         self == other
@@ -907,4 +1383,183 @@ mod tests {
             assert_eq!(hash["name"].as_string().unwrap(), "test");
         }
     }
+
+    #[test]
+    fn get_typed() {
+        let byml = Byml::from_iter([
+            ("HP", Byml::I32(40)),
+            ("Name", Byml::String("Bokoblin".into())),
+            ("Drops", Byml::Array(vec![Byml::Float(0.5)])),
+        ]);
+        assert_eq!(byml.get_typed::<i32>("HP").unwrap(), 40);
+        assert_eq!(byml.get_typed::<&str>("Name").unwrap(), "Bokoblin");
+        assert_eq!(byml.get_typed::<String>("Name").unwrap(), "Bokoblin");
+        assert_eq!(byml.get_typed::<Vec<Byml>>("Drops").unwrap(), vec![
+            Byml::Float(0.5)
+        ]);
+        assert!(matches!(
+            byml.get_typed::<u32>("HP"),
+            Err(Error::TypeError(..))
+        ));
+        assert!(byml.get_typed::<i32>("Missing").is_err());
+        assert!(Byml::I32(1).get_typed::<i32>("HP").is_err());
+    }
+
+    #[test]
+    fn sort_array() {
+        assert!(Byml::I32(1) < Byml::I32(2));
+        assert!(Byml::String("b".into()) > Byml::String("a".into()));
+        assert_eq!(Byml::I32(1).partial_cmp(&Byml::U32(1)), None);
+        assert_eq!(
+            Byml::Array(vec![]).partial_cmp(&Byml::Array(vec![Byml::Null])),
+            None
+        );
+
+        let mut array: Byml = ["Enemy_Moblin", "Enemy_Bokoblin", "Enemy_Lizalfos"]
+            .into_iter()
+            .map(Byml::from)
+            .collect();
+        array.sort_array().unwrap();
+        assert_eq!(
+            array,
+            ["Enemy_Bokoblin", "Enemy_Lizalfos", "Enemy_Moblin"]
+                .into_iter()
+                .map(Byml::from)
+                .collect::<Byml>()
+        );
+        let mut array = Byml::Array(vec![Byml::Float(2.5), Byml::Float(-1.0), Byml::Float(0.0)]);
+        array.sort_array().unwrap();
+        assert_eq!(
+            array,
+            Byml::Array(vec![Byml::Float(-1.0), Byml::Float(0.0), Byml::Float(2.5)])
+        );
+        assert!(Byml::Null.sort_array().is_err());
+
+        let mut array = Byml::Array(vec![
+            Byml::Null,
+            Byml::Float(f32::NAN),
+            Byml::I32(2),
+            Byml::Array(vec![Byml::I32(1), Byml::I32(2)]),
+            Byml::Float(0.0),
+            Byml::Array(vec![Byml::I32(1)]),
+            Byml::Float(-0.0),
+            Byml::String("a".into()),
+            Byml::I32(1),
+        ]);
+        array.sort_array().unwrap();
+        let array = array.into_array().unwrap();
+        assert_eq!(array[..6], [
+            Byml::String("a".into()),
+            Byml::Array(vec![Byml::I32(1)]),
+            Byml::Array(vec![Byml::I32(1), Byml::I32(2)]),
+            Byml::I32(1),
+            Byml::I32(2),
+            Byml::Float(-0.0),
+        ]);
+        assert!(array[6].as_float().unwrap().to_bits() == 0.0f32.to_bits());
+        assert!(array[7].as_float().unwrap().is_nan());
+        assert_eq!(array[8], Byml::Null);
+        // The order is total, so sorting any permutation gives the same result.
+        let mut reversed = Byml::Array(array.iter().rev().cloned().collect());
+        reversed.sort_array().unwrap();
+        assert!(
+            reversed
+                .as_array()
+                .unwrap()
+                .iter()
+                .zip(&array)
+                .all(|(a, b)| total_cmp(a, b).is_eq())
+        );
+    }
+
+    #[test]
+    fn entry_hash() {
+        let mut byml = Byml::from_iter([("Count", Byml::I32(1))]);
+        if let Byml::I32(count) = byml.entry_hash("Count").unwrap().or_insert(Byml::I32(0)) {
+            *count += 1;
+        }
+        byml.entry_hash("Name")
+            .unwrap()
+            .or_insert_with(|| "Test".into());
+        assert_eq!(byml["Count"], Byml::I32(2));
+        assert_eq!(byml["Name"], Byml::String("Test".into()));
+        assert!(Byml::Array(vec![]).entry_hash("Count").is_err());
+    }
+
+    #[test]
+    fn byml_error() {
+        let err: Error = BymlError::InvalidVersion(9).into();
+        assert_eq!(err.to_string(), "Invalid version: 9");
+        assert!(matches!(
+            Error::from(BymlError::ParseError("Bad")),
+            Error::InvalidData("Bad")
+        ));
+    }
+
+    #[test]
+    fn hash_order() {
+        let keys = ["b", "c", "a", "B", "aa"];
+        let forward: Byml = keys.iter().map(|&k| (k, Byml::Null)).collect();
+        let reverse: Byml = keys.iter().rev().map(|&k| (k, Byml::Null)).collect();
+        let binary = forward.to_binary(crate::Endian::Little);
+        assert_eq!(binary, reverse.to_binary(crate::Endian::Little));
+        // The key table and the hash entries are both sorted.
+        let parsed = Byml::from_binary(&binary).unwrap();
+        let mut parsed_keys: Vec<_> = parsed.iter_hash().unwrap().map(|(k, _)| k).collect();
+        parsed_keys.sort();
+        assert_eq!(parsed_keys, ["B", "a", "aa", "b", "c"]);
+        let key_positions: Vec<_> = parsed_keys
+            .iter()
+            .map(|k| {
+                binary
+                    .windows(k.len() + 1)
+                    .position(|w| w == format!("{k}\0").as_bytes())
+            })
+            .collect();
+        assert!(key_positions.windows(2).all(|w| w[0] < w[1]));
+
+        #[cfg(feature = "yaml")]
+        {
+            let text = forward.to_text().unwrap();
+            assert_eq!(text, reverse.to_text().unwrap());
+            assert_eq!(text, "B: null\na: null\naa: null\nb: null\nc: null\n");
+        }
+    }
+
+    #[test]
+    fn approx_eq() {
+        let doc = |f: f32, d: f64| {
+            Byml::from_iter([(
+                "A",
+                Byml::Array(vec![Byml::Float(f), Byml::Double(d), Byml::I32(1)]),
+            )])
+        };
+        assert!(doc(1.0, 2.0).approx_eq(&doc(1.0005, 2.0005), 0.001));
+        assert!(!doc(1.0, 2.0).approx_eq(&doc(1.0, 2.01), 0.001));
+        assert!(doc(f32::NAN, f64::INFINITY).approx_eq(&doc(f32::NAN, f64::INFINITY), 0.0));
+        assert!(!doc(1.0, 2.0).approx_eq(&Byml::from_iter([("A", Byml::Null)]), 1.0));
+        assert!(!Byml::I32(1).approx_eq(&Byml::I32(2), 10.0));
+    }
+
+    #[test]
+    fn iterators() {
+        let mut array = Byml::Array(vec![Byml::I32(1), Byml::I32(2)]);
+        assert_eq!(array.iter_array().unwrap().count(), 2);
+        for node in array.iter_array_mut().unwrap() {
+            *node = Byml::Null;
+        }
+        assert_eq!(array, Byml::Array(vec![Byml::Null, Byml::Null]));
+        assert!(array.iter_hash().is_none());
+
+        let mut hash = Byml::from_iter([("A", Byml::I32(1))]);
+        assert!(hash.iter_array().is_none());
+        for (_, node) in hash.iter_hash_mut().unwrap() {
+            *node = Byml::I32(5);
+        }
+        assert_eq!(hash.iter_hash().unwrap().next().unwrap().0, "A");
+        assert_eq!(hash.iter_hash_values().unwrap().collect::<Vec<_>>(), [
+            &Byml::I32(5)
+        ]);
+        assert!(Byml::Null.iter_hash_values().is_none());
+    }
 }
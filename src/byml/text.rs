@@ -1,3 +1,4 @@
+use rustc_hash::FxHashMap;
 use ryml::{NodeRef, Tree};
 
 use super::*;
@@ -5,20 +6,164 @@ use crate::{yaml::*, Error, Result};
 
 impl Byml {
     /// Parse BYML document from YAML text.
+    ///
+    /// Untagged, unquoted scalars are resolved with simplified rules which
+    /// do not follow any YAML schema exactly:
+    ///
+    /// - `true` and `false` are booleans. Other spellings, including `True`, `TRUE`, `yes` and
+    ///   `off`, are strings.
+    /// - `null`, `Null`, `NULL` and `~` are null.
+    /// - Decimal integers are I32 nodes. Leading zeros do not make them octal, so `0777` is 777.
+    ///   Hexadecimal values such as `0x10` are only integers when tagged (`!u`, `!l` or `!ul`).
+    /// - Values containing a `.` which parse as decimal numbers, as well as `.inf`, `-.inf` and
+    ///   `.nan` (in lower, title or upper case), are Float nodes. `1e5` has no `.`, so it is a
+    ///   string.
+    /// - Everything else is a string.
+    ///
+    /// Use [`Byml::from_text_with_options`] with [`YamlVersion::V1_1`] for
+    /// documents which rely on YAML 1.1 booleans.
+    ///
+    /// Aliases are expanded into copies of their anchored nodes. A document
+    /// whose aliases would expand to more than 2<sup>20</sup> nodes in total
+    /// is rejected with [`Error::InvalidData`].
     pub fn from_text(text: impl AsRef<str>) -> Result<Byml> {
-        Parser::new(text.as_ref())?.parse()
+        Parser::new(text.as_ref(), YamlVersion::V1_2)?.parse()
+    }
+
+    /// Parse BYML document from YAML text using the given options. Only
+    /// [`BymlParseOptions::yaml_version`] applies to text.
+    ///
+    /// ```
+    /// # use roead::byml::*;
+    /// let options = BymlParseOptions {
+    ///     yaml_version: YamlVersion::V1_1,
+    ///     ..Default::default()
+    /// };
+    /// let byml = Byml::from_text_with_options("{A: yes, B: 'yes'}", &options)?;
+    /// assert_eq!(byml["A"], Byml::Bool(true));
+    /// assert_eq!(byml["B"], Byml::String("yes".into()));
+    /// # Ok::<(), roead::Error>(())
+    /// ```
+    pub fn from_text_with_options(
+        text: impl AsRef<str>,
+        options: &BymlParseOptions,
+    ) -> Result<Byml> {
+        Parser::new(text.as_ref(), options.yaml_version)?.parse()
+    }
+
+    /// Parse BYML document from YAML text, giving up with [`Error::Timeout`]
+    /// if parsing takes longer than `timeout`.
+    ///
+    /// Parsing runs on a separate thread. Threads cannot be killed, so on a
+    /// timeout the thread keeps running in the background until it finishes
+    /// and its result is discarded. Alias expansion is limited as for
+    /// [`Byml::from_text`], so that work is bounded even for small documents
+    /// with nested aliases.
+    pub fn from_text_with_timeout(
+        text: impl AsRef<str>,
+        timeout: std::time::Duration,
+    ) -> Result<Byml> {
+        let text = text.as_ref().to_owned();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            // The receiver is gone if we timed out, in which case the result
+            // is simply dropped.
+            let _ = sender.send(Byml::from_text(text));
+        });
+        receiver.recv_timeout(timeout).map_err(|err| {
+            match err {
+                std::sync::mpsc::RecvTimeoutError::Timeout => Error::Timeout(timeout),
+                std::sync::mpsc::RecvTimeoutError::Disconnected => {
+                    Error::InvalidData("YAML parsing thread panicked")
+                }
+            }
+        })?
+    }
+
+    /// Convert a node of an already parsed [`ryml::Tree`] into BYML, without
+    /// going through text. The node is interpreted exactly as it would be by
+    /// [`Byml::from_text`].
+    pub fn from_node_ref<'a>(node: NodeRef<'a, '_, '_, &Tree<'a>>) -> Result<Byml> {
+        Parser::parse_node(node, &mut Default::default(), YamlVersion::V1_2)
     }
 
     /// Serialize the document to YAML. This can only be done for Null, Array,
     /// or Hash nodes.
     pub fn to_text(&self) -> Result<std::string::String> {
-        Emitter::new(self).emit()
+        self.to_text_with_options(&BymlTextOptions::default())
+    }
+
+    /// Serialize the document to YAML using the given options. This can only
+    /// be done for Null, Array, or Hash nodes.
+    pub fn to_text_with_options(&self, options: &BymlTextOptions) -> Result<std::string::String> {
+        Emitter::new(self, options).emit()
+    }
+}
+
+/// Options for serializing a [`Byml`] document to YAML.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BymlTextOptions {
+    /// Unicode normalization form applied to all strings (including hash
+    /// keys) before they are emitted.
+    ///
+    /// This is only available with the `unicode-normalization` feature.
+    #[cfg(feature = "unicode-normalization")]
+    pub normalize_unicode: crate::types::NormalizationForm,
+    /// Number of decimal places to emit for floating point values, or `None`
+    /// for the shortest representation which round-trips. At least one
+    /// decimal place is always emitted so that values are still read back as
+    /// floating point numbers. Infinite and NaN values are unaffected.
+    pub float_precision: Option<usize>,
+    /// Format of unsigned integer (U32 and U64) values.
+    pub integer_format: IntFormat,
+}
+
+/// Format of unsigned integer values in YAML.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IntFormat {
+    /// Decimal, e.g. `!u 255`.
+    Decimal,
+    /// Hexadecimal, e.g. `!u 0xFF`.
+    #[default]
+    Hex,
+}
+
+impl BymlTextOptions {
+    #[inline]
+    fn normalize<'s>(&self, s: &'s str) -> std::borrow::Cow<'s, str> {
+        #[cfg(feature = "unicode-normalization")]
+        {
+            self.normalize_unicode.normalize(s)
+        }
+        #[cfg(not(feature = "unicode-normalization"))]
+        {
+            s.into()
+        }
+    }
+
+    #[inline]
+    fn format_float<F: lexical::ToLexical + std::fmt::Display + Into<f64> + Copy>(
+        &self,
+        value: F,
+    ) -> std::string::String {
+        let double: f64 = value.into();
+        if double.is_nan() {
+            return ".nan".into();
+        } else if double.is_infinite() {
+            return if double > 0.0 { ".inf" } else { "-.inf" }.into();
+        }
+        match self.float_precision {
+            Some(precision) => format!("{:.*}", precision.max(1), value),
+            // lexical drops the sign of negative zero.
+            None if double == 0.0 && double.is_sign_negative() => "-0.0".into(),
+            None => lexical::to_string(value),
+        }
     }
 }
 
 #[inline]
 fn is_binary_tag(tag: &str) -> bool {
-    tag == "tag:yaml.org,2002:binary" || tag == "!!binary"
+    matches!(tag, "tag:yaml.org,2002:binary" | "!!binary" | "!base64")
 }
 
 #[inline]
@@ -26,39 +171,119 @@ fn recognize_tag(tag: &str) -> Option<TagBasedType> {
     match tag {
         "!f64" => Some(TagBasedType::Float),
         "!u" | "!l" | "!ul" => Some(TagBasedType::Int),
-        "tag:yaml.org,2002:binary" | "!!binary" => Some(TagBasedType::Str),
+        "tag:yaml.org,2002:binary" | "!!binary" | "!base64" => Some(TagBasedType::Str),
         _ => None,
     }
 }
 
-struct Parser<'a>(Tree<'a>);
+/// Maximum number of nodes which may be produced by expanding aliases
+/// (including merge keys) in one document. Without a limit, a small document
+/// with nested aliases could expand exponentially ("billion laughs").
+const MAX_ALIAS_NODES: usize = 1 << 20;
+
+/// Anchored nodes seen so far, along with the number of nodes in each.
+#[derive(Default)]
+struct Anchors {
+    nodes: FxHashMap<std::string::String, (Byml, usize)>,
+    /// Number of nodes parsed so far, counting every node of an expanded
+    /// alias.
+    node_count: usize,
+    /// Number of nodes produced by expanding aliases so far.
+    alias_node_count: usize,
+}
+
+struct Parser<'a>(Tree<'a>, YamlVersion);
 
 impl<'a> Parser<'a> {
-    fn new(text: &str) -> Result<Self> {
-        Ok(Self(Tree::parse(text)?))
+    fn new(text: &str, version: YamlVersion) -> Result<Self> {
+        Ok(Self(parse_yaml(text)?, version))
     }
 
-    fn parse_node(node: NodeRef<'a, '_, '_, &Tree<'a>>) -> Result<Byml> {
+    /// Anchored nodes are recorded in `anchors` as they are parsed, so that
+    /// later aliases and merge keys (`<<`) can refer to them. Every alias
+    /// counts the full size of its target against [`MAX_ALIAS_NODES`].
+    fn parse_node(
+        node: NodeRef<'a, '_, '_, &Tree<'a>>,
+        anchors: &mut Anchors,
+        version: YamlVersion,
+    ) -> Result<Byml> {
+        if node.is_val_ref()? {
+            let anchor = node.val_ref()?;
+            let (byml, count) = anchors
+                .nodes
+                .get(anchor)
+                .ok_or_else(|| Error::InvalidDataD(format!("Unknown YAML anchor: {anchor}")))?;
+            anchors.alias_node_count += count;
+            if anchors.alias_node_count > MAX_ALIAS_NODES {
+                return Err(Error::InvalidData(
+                    "Too many nodes produced by YAML aliases",
+                ));
+            }
+            anchors.node_count += count;
+            return Ok(byml.clone());
+        }
+        let start = anchors.node_count;
+        anchors.node_count += 1;
+        let byml = Self::parse_node_value(node.clone(), anchors, version)?;
+        if node.has_val_anchor()? {
+            let count = anchors.node_count - start;
+            anchors
+                .nodes
+                .insert(node.val_anchor()?.into(), (byml.clone(), count));
+        }
+        Ok(byml)
+    }
+
+    fn parse_node_value(
+        node: NodeRef<'a, '_, '_, &Tree<'a>>,
+        anchors: &mut Anchors,
+        version: YamlVersion,
+    ) -> Result<Byml> {
         if node.is_map()? {
-            Ok(Byml::Hash(
-                node.iter()?
-                    .map(|child| {
-                        let key = child.key()?;
-                        let value = Self::parse_node(child.clone())?;
-                        Ok((key.into(), value))
-                    })
-                    .collect::<Result<_>>()?,
-            ))
+            let mut hash = Hash::default();
+            let mut merged = vec![];
+            for child in node.iter()? {
+                let key = child.key()?;
+                let value = Self::parse_node(child.clone(), anchors, version)?;
+                if key == "<<" && !child.is_key_quoted()? {
+                    match value {
+                        Byml::Hash(h) => merged.push(h),
+                        Byml::Array(array) => {
+                            for item in array {
+                                merged.push(item.into_hash()?);
+                            }
+                        }
+                        _ => return Err(Error::TypeError(value.type_name(), "Hash or Array")),
+                    }
+                } else {
+                    hash.insert(key.into(), value);
+                }
+            }
+            // Explicit keys take precedence over merged ones, and earlier
+            // merged maps over later ones.
+            for (key, value) in merged.into_iter().flatten() {
+                hash.entry(key).or_insert(value);
+            }
+            Ok(Byml::Hash(hash))
         } else if node.is_seq()? {
             Ok(Byml::Array(
                 node.iter()?
-                    .map(|child| Self::parse_node(child.clone()))
+                    .map(|child| Self::parse_node(child.clone(), anchors, version))
                     .collect::<Result<_>>()?,
             ))
         } else {
             let tag = node.val_tag().unwrap_or("");
             let tag_type = get_tag_based_type(tag).or_else(|| recognize_tag(tag));
-            let scalar = parse_scalar(tag_type, node.val()?, node.is_quoted()?)?;
+            let value = node.val()?;
+            let is_quoted = node.is_quoted()?;
+            if version == YamlVersion::V1_1
+                && tag.is_empty()
+                && !is_quoted
+                && let Some(b) = parse_yaml_1_1_bool(value)
+            {
+                return Ok(Byml::Bool(b));
+            }
+            let scalar = parse_scalar(tag_type, value, is_quoted)?;
             match scalar {
                 Scalar::Bool(b) => Ok(Byml::Bool(b)),
                 Scalar::Float(f) => {
@@ -89,7 +314,7 @@ impl<'a> Parser<'a> {
 
     fn parse(self) -> Result<Byml> {
         let root = self.0.root_ref()?;
-        Self::parse_node(root)
+        Self::parse_node(root, &mut Default::default(), self.1)
     }
 }
 
@@ -103,18 +328,19 @@ fn should_use_inline(byml: &Byml) -> bool {
     }
 }
 
-struct Emitter<'a, 'b>(&'a Byml, Tree<'b>);
+struct Emitter<'a, 'b>(&'a Byml, Tree<'b>, &'a BymlTextOptions);
 
 impl<'a, 'b> Emitter<'a, 'b> {
-    fn new(byml: &'a Byml) -> Self {
+    fn new(byml: &'a Byml, options: &'a BymlTextOptions) -> Self {
         let mut tree = Tree::default();
         tree.reserve(20000);
-        Self(byml, tree)
+        Self(byml, tree, options)
     }
 
     fn build_node<'e>(
         byml: &Byml,
         mut dest_node: NodeRef<'b, 'e, '_, &'e mut Tree<'b>>,
+        options: &BymlTextOptions,
     ) -> Result<()> {
         match byml {
             Byml::Array(array) => {
@@ -125,7 +351,7 @@ impl<'a, 'b> Emitter<'a, 'b> {
                 }
                 for item in array {
                     let node = dest_node.append_child()?;
-                    Self::build_node(item, node)?;
+                    Self::build_node(item, node, options)?;
                 }
             }
             Byml::Hash(hash) => {
@@ -138,28 +364,38 @@ impl<'a, 'b> Emitter<'a, 'b> {
                 map_items.sort_by(|a, b| a.0.cmp(b.0));
                 for (key, value) in map_items {
                     let mut node = dest_node.append_child()?;
-                    node.set_key(key)?;
-                    if string_needs_quotes(key) {
+                    let key = options.normalize(key);
+                    node.set_key(&key)?;
+                    if string_needs_quotes(&key) {
                         let flags = node.node_type()?;
                         node.set_type_flags(flags | ryml::NodeType::WipKeySquo)?;
                     }
-                    Self::build_node(value, node)?;
+                    Self::build_node(value, node, options)?;
                 }
             }
             scalar => {
                 match scalar {
                     Byml::String(s) => {
-                        dest_node.set_val(s)?;
-                        if string_needs_quotes(s) {
+                        let s = options.normalize(s);
+                        dest_node.set_val(&s)?;
+                        if string_needs_quotes(&s) {
                             let flags = dest_node.node_type()?;
                             dest_node.set_type_flags(flags | ryml::NodeType::WipValDquo)?;
                         }
                     }
                     Byml::Bool(b) => dest_node.set_val(if *b { "true" } else { "false" })?,
-                    Byml::Float(f) => dest_node.set_val(&lexical::to_string(*f))?,
+                    Byml::Float(f) => {
+                        dest_node.set_val(&options.format_float(*f))?;
+                        // Keep `-.inf` from being quoted, which would make it
+                        // a string.
+                        let flags = dest_node.node_type()?;
+                        dest_node.set_type_flags(flags | ryml::NodeType::WipValPlain)?;
+                    }
                     Byml::Double(d) => {
-                        dest_node.set_val(&lexical::to_string(*d))?;
+                        dest_node.set_val(&options.format_float(*d))?;
                         dest_node.set_val_tag("!f64")?;
+                        let flags = dest_node.node_type()?;
+                        dest_node.set_type_flags(flags | ryml::NodeType::WipValPlain)?;
                     }
                     Byml::I32(i) => dest_node.set_val(&lexical::to_string(*i))?,
                     Byml::I64(i) => {
@@ -167,11 +403,17 @@ impl<'a, 'b> Emitter<'a, 'b> {
                         dest_node.set_val_tag("!l")?;
                     }
                     Byml::U32(u) => {
-                        dest_node.set_val(&format_hex!(u))?;
+                        dest_node.set_val(&match options.integer_format {
+                            IntFormat::Decimal => lexical::to_string(*u),
+                            IntFormat::Hex => format_hex!(u),
+                        })?;
                         dest_node.set_val_tag("!u")?;
                     }
                     Byml::U64(u) => {
-                        dest_node.set_val(&format_hex!(u))?;
+                        dest_node.set_val(&match options.integer_format {
+                            IntFormat::Decimal => lexical::to_string(*u),
+                            IntFormat::Hex => format_hex!(u),
+                        })?;
                         dest_node.set_val_tag("!ul")?;
                     }
                     Byml::Null => dest_node.set_val("null")?,
@@ -189,7 +431,7 @@ impl<'a, 'b> Emitter<'a, 'b> {
     }
 
     fn emit(self) -> Result<std::string::String> {
-        let Self(byml, mut tree) = self;
+        let Self(byml, mut tree, options) = self;
         match byml {
             Byml::Hash(_) => tree.to_map(0)?,
             Byml::Array(_) => tree.to_seq(0)?,
@@ -200,7 +442,7 @@ impl<'a, 'b> Emitter<'a, 'b> {
                 ));
             }
         };
-        Self::build_node(byml, tree.root_ref_mut()?)?;
+        Self::build_node(byml, tree.root_ref_mut()?, options)?;
         Ok(tree.emit()?)
     }
 }
@@ -221,11 +463,168 @@ mod test {
             let bytes =
                 std::fs::read(std::path::Path::new("test/byml").join([file, ".byml"].join("")))
                     .unwrap();
+            if !cfg!(feature = "yaz0") && bytes.starts_with(b"Yaz0") {
+                continue;
+            }
             let binary_byml = Byml::from_binary(bytes).unwrap();
             assert_eq!(byml, binary_byml);
         }
     }
 
+    #[test]
+    fn from_node_ref() {
+        let tree =
+            Tree::parse("{Actors: [{Name: Enemy_Lizalfos, Scale: 1.5}], Count: !u 0x1}").unwrap();
+        let actors = tree.root_ref().unwrap().get("Actors").unwrap();
+        let byml = Byml::from_node_ref(actors).unwrap();
+        assert_eq!(byml[0]["Name"], Byml::String("Enemy_Lizalfos".into()));
+        assert_eq!(byml[0]["Scale"], Byml::Float(1.5));
+        let count = tree.root_ref().unwrap().get("Count").unwrap();
+        assert_eq!(Byml::from_node_ref(count).unwrap(), Byml::U32(1));
+    }
+
+    #[cfg(feature = "unicode-normalization")]
+    #[test]
+    fn normalize_unicode() {
+        let byml = Byml::Hash(
+            [("Cafe\u{301}".into(), Byml::String("Pok\u{e9}mon".into()))]
+                .into_iter()
+                .collect(),
+        );
+        let nfc = byml
+            .to_text_with_options(&BymlTextOptions {
+                normalize_unicode: crate::types::NormalizationForm::Nfc,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(nfc.contains("Caf\u{e9}") && nfc.contains("Pok\u{e9}mon"));
+        let nfd = byml
+            .to_text_with_options(&BymlTextOptions {
+                normalize_unicode: crate::types::NormalizationForm::Nfd,
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(nfd.contains("Cafe\u{301}") && nfd.contains("Poke\u{301}mon"));
+        assert!(byml.to_text().unwrap().contains("Cafe\u{301}"));
+    }
+
+    #[test]
+    fn block_scalars() {
+        for (indicator, text, number, boolean) in [
+            ("|", "line one\nline two\n", "123\n", "true\n"),
+            ("|-", "line one\nline two", "123", "true"),
+            ("|+", "line one\nline two\n\n\n", "123\n", "true\n"),
+            (">", "line one line two\n", "123\n", "true\n"),
+            (">-", "line one line two", "123", "true"),
+            (">+", "line one line two\n\n\n", "123\n", "true\n"),
+        ] {
+            let yaml = format!(
+                "a: {indicator}\n  line one\n  line two\n\n\nb: {indicator}\n  123\nc: \
+                 {indicator}\n  true\n"
+            );
+            let byml = Byml::from_text(&yaml).unwrap();
+            assert_eq!(
+                byml,
+                Byml::from_iter([
+                    ("a", Byml::String(text.into())),
+                    ("b", Byml::String(number.into())),
+                    ("c", Byml::String(boolean.into())),
+                ]),
+                "{indicator}"
+            );
+            assert_eq!(Byml::from_text(byml.to_text().unwrap()).unwrap(), byml);
+        }
+        let quoted = Byml::from_iter([
+            ("a", Byml::String("true".into())),
+            ("b", Byml::String("null".into())),
+        ]);
+        assert_eq!(Byml::from_text(quoted.to_text().unwrap()).unwrap(), quoted);
+    }
+
+    #[test]
+    fn number_format() {
+        let byml = Byml::from_iter([
+            ("Float", Byml::Float(1.5)),
+            ("Double", Byml::Double(2.0)),
+            ("U32", Byml::U32(255)),
+            ("U64", Byml::U64(4096)),
+        ]);
+        let text = byml.to_text().unwrap();
+        assert!(text.contains("Float: 1.5\n") && text.contains("U32: !u 0x"));
+        let options = BymlTextOptions {
+            float_precision: Some(3),
+            integer_format: IntFormat::Decimal,
+            ..Default::default()
+        };
+        let text = byml.to_text_with_options(&options).unwrap();
+        assert!(text.contains("Float: 1.500\n"));
+        assert!(text.contains("Double: !f64 2.000\n"));
+        assert!(text.contains("U32: !u 255\n"));
+        assert!(text.contains("U64: !ul 4096\n"));
+        assert_eq!(Byml::from_text(&text).unwrap(), byml);
+        let options = BymlTextOptions {
+            float_precision: Some(0),
+            ..Default::default()
+        };
+        let text = byml.to_text_with_options(&options).unwrap();
+        assert_eq!(Byml::from_text(text).unwrap(), byml);
+    }
+
+    #[test]
+    fn timeout() {
+        let text = "a: 1\nb: [2, 3]\n";
+        assert_eq!(
+            Byml::from_text_with_timeout(text, std::time::Duration::from_secs(30)).unwrap(),
+            Byml::from_text(text).unwrap()
+        );
+        let text = std::fs::read_to_string("test/byml/ActorInfo.product.yml").unwrap();
+        assert!(matches!(
+            Byml::from_text_with_timeout(text, std::time::Duration::ZERO),
+            Err(Error::Timeout(_))
+        ));
+    }
+
+    #[test]
+    fn merge_keys() {
+        let text = "x: &x\n  a: 1\n  b: 2\ny: &y\n  b: 3\n  c: 4\nm1:\n  b: 0\n  <<: *x\nm2:\n  \
+                    <<: [*y, *x]\nlist:\n  - *x\n";
+        let byml = Byml::from_text(text).unwrap();
+        let hash = |entries: &[(&str, i32)]| {
+            entries
+                .iter()
+                .map(|(k, v)| (*k, Byml::I32(*v)))
+                .collect::<Byml>()
+        };
+        assert_eq!(byml["m1"], hash(&[("a", 1), ("b", 0)]));
+        assert_eq!(byml["m2"], hash(&[("a", 1), ("b", 3), ("c", 4)]));
+        assert_eq!(byml["list"][0], hash(&[("a", 1), ("b", 2)]));
+        assert!(Byml::from_text("a: *missing\n").is_err());
+    }
+
+    #[test]
+    fn alias_expansion_limit() {
+        // Each level holds ten copies of the previous one, so the last level
+        // would expand to 10^9 nodes.
+        let mut text = "a0: &a0 [lol]\n".to_string();
+        for i in 1..10 {
+            let prev = format!("*a{}", i - 1);
+            text += &format!("a{i}: &a{i} [{}]\n", vec![prev.as_str(); 10].join(", "));
+        }
+        assert!(matches!(
+            Byml::from_text(&text),
+            Err(Error::InvalidData(
+                "Too many nodes produced by YAML aliases"
+            ))
+        ));
+        // Small expansions are unaffected: a3 expands to 10^3 copies of a0.
+        let text = text.lines().take(4).collect::<Vec<_>>().join("\n");
+        let byml = Byml::from_text(text).unwrap();
+        assert_eq!(
+            byml["a3"][9][9][9],
+            Byml::Array(vec![Byml::String("lol".into())])
+        );
+    }
+
     #[test]
     fn text_roundtrip() {
         for file in crate::byml::FILES {
@@ -241,4 +640,112 @@ mod test {
             assert_eq!(byml, byml);
         }
     }
+
+    #[test]
+    fn float_roundtrip() {
+        let doubles = [
+            0x0000_0000_0000_0001, // smallest subnormal
+            0x000F_FFFF_FFFF_FFFF, // largest subnormal
+            0x0010_0000_0000_0000, // smallest normal
+            0x7FEF_FFFF_FFFF_FFFF, // largest finite
+            0x8000_0000_0000_0000, // -0.0
+            0x3FB9_9999_9999_999A, // 0.1
+            0x4009_21FB_5444_2D18, // pi
+            0x7FF0_0000_0000_0000, // inf
+            0xFFF0_0000_0000_0000, // -inf
+        ]
+        .map(f64::from_bits);
+        let floats = [
+            0x0000_0001,
+            0x7F7F_FFFF,
+            0x8000_0000,
+            0x3DCC_CCCD,
+            0x7F80_0000,
+            0xFF80_0000,
+        ]
+        .map(f32::from_bits);
+        let byml: Byml = doubles
+            .into_iter()
+            .map(Byml::Double)
+            .chain(floats.into_iter().map(Byml::Float))
+            .collect();
+        let text = byml.to_text().unwrap();
+        let parsed = Byml::from_text(&text).unwrap();
+        for (a, b) in byml
+            .as_array()
+            .unwrap()
+            .iter()
+            .zip(parsed.as_array().unwrap())
+        {
+            match (a, b) {
+                (Byml::Double(a), Byml::Double(b)) => assert_eq!(a.to_bits(), b.to_bits()),
+                (Byml::Float(a), Byml::Float(b)) => assert_eq!(a.to_bits(), b.to_bits()),
+                _ => panic!("Type changed in round trip: {a:?} -> {b:?}"),
+            }
+        }
+
+        let nan: Byml = [Byml::Double(f64::NAN), Byml::Float(f32::NAN)]
+            .into_iter()
+            .collect();
+        let text = nan.to_text().unwrap();
+        assert!(text.contains("!f64 .nan\n"));
+        let parsed = Byml::from_text(text).unwrap();
+        assert!(matches!(parsed[0], Byml::Double(d) if d.is_nan()));
+        assert!(matches!(parsed[1], Byml::Float(f) if f.is_nan()));
+    }
+
+    #[test]
+    fn base64_tag() {
+        let text = std::fs::read_to_string("test/byml/base64.yml").unwrap();
+        let byml = Byml::from_text(text).unwrap();
+        let expected = Byml::BinaryData(b"roead".to_vec());
+        assert_eq!(byml["Data"], expected);
+        assert_eq!(byml["Standard"], expected);
+        assert!(byml.to_text().unwrap().contains("!!binary"));
+    }
+
+    #[test]
+    fn untagged_scalars() {
+        let byml = Byml::from_text("{A: yes, B: off, C: 0777, D: true}").unwrap();
+        assert_eq!(byml["A"], Byml::String("yes".into()));
+        assert_eq!(byml["B"], Byml::String("off".into()));
+        assert_eq!(byml["C"], Byml::I32(777));
+        assert_eq!(byml["D"], Byml::Bool(true));
+        let byml =
+            Byml::from_text("{A: True, B: ~, C: 0x10, D: 1e5, E: 1.5, F: .Inf, G: Null}").unwrap();
+        assert_eq!(byml["A"], Byml::String("True".into()));
+        assert_eq!(byml["B"], Byml::Null);
+        assert_eq!(byml["C"], Byml::String("0x10".into()));
+        assert_eq!(byml["D"], Byml::String("1e5".into()));
+        assert_eq!(byml["E"], Byml::Float(1.5));
+        assert_eq!(byml["F"], Byml::Float(f32::INFINITY));
+        assert_eq!(byml["G"], Byml::Null);
+    }
+
+    #[test]
+    fn yaml_1_1_bools() {
+        let text = "{A: yes, B: Off, C: TRUE, D: 'on', E: !!str no, F: y, G: yess}";
+        let options = BymlParseOptions {
+            yaml_version: YamlVersion::V1_1,
+            ..Default::default()
+        };
+        let byml = Byml::from_text_with_options(text, &options).unwrap();
+        assert_eq!(byml["A"], Byml::Bool(true));
+        assert_eq!(byml["B"], Byml::Bool(false));
+        assert_eq!(byml["C"], Byml::Bool(true));
+        assert_eq!(byml["D"], Byml::String("on".into()));
+        assert_eq!(byml["E"], Byml::String("no".into()));
+        assert_eq!(byml["F"], Byml::String("y".into()));
+        assert_eq!(byml["G"], Byml::String("yess".into()));
+
+        // Strings which YAML 1.1 would read as booleans are quoted, so they
+        // round trip in either mode.
+        let strings: Byml = [("A", Byml::String("yes".into()))].into_iter().collect();
+        let text = strings.to_text().unwrap();
+        assert_eq!(Byml::from_text(&text).unwrap(), strings);
+        assert_eq!(
+            Byml::from_text_with_options(&text, &options).unwrap(),
+            strings
+        );
+    }
 }
@@ -13,6 +13,56 @@ use crate::{
     Endian, Error, Result,
 };
 
+/// Options for serializing a [`Byml`] document to binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BymlWriteOptions {
+    /// Endianness of the document.
+    pub endian:  Endian,
+    /// BYML version number.
+    pub version: u16,
+}
+
+impl Default for BymlWriteOptions {
+    fn default() -> Self {
+        Self {
+            endian:  Endian::Little,
+            version: 2,
+        }
+    }
+}
+
+/// Target console for a binary BYML document, for
+/// [`BymlWriteOptions::for_platform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Wii U: big endian.
+    WiiU,
+    /// Switch: little endian.
+    Switch,
+}
+
+impl BymlWriteOptions {
+    /// Options matching the game ROM for the given platform. Both platforms
+    /// use version 2, as in *Breath of the Wild*; set `version` afterwards
+    /// for games which use a newer one. Alignment is the same on both
+    /// platforms, so only the endianness differs.
+    pub fn for_platform(platform: Platform) -> Self {
+        Self {
+            endian:  match platform {
+                Platform::WiiU => Endian::Big,
+                Platform::Switch => Endian::Little,
+            },
+            version: 2,
+        }
+    }
+}
+
+impl From<Platform> for BymlWriteOptions {
+    fn from(platform: Platform) -> Self {
+        Self::for_platform(platform)
+    }
+}
+
 impl Byml {
     /// Serialize the document to binary into the given writer. This can only
     /// be done for Null, Array, or Hash nodes.
@@ -86,6 +136,45 @@ impl Byml {
             .expect("BYML should serialize to binary without error");
         buf
     }
+
+    /// Serialize the document to bytes using the given options. This can only
+    /// be done for Null, Array, or Hash nodes.
+    pub fn to_binary_with_options(&self, options: &BymlWriteOptions) -> Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.write(&mut Cursor::new(&mut buf), options.endian, options.version)?;
+        Ok(buf)
+    }
+
+    /// Serialize the document with the default options (little endian,
+    /// version 2) after normalizing floats, so that documents with the same
+    /// content give the same bytes. Hash entries are always written in sorted
+    /// key order anyway; on top of that, `-0.0` is written as `0.0` and all
+    /// NaNs as the canonical quiet NaN, for both floats and doubles.
+    pub fn to_binary_canonical(&self) -> Result<Vec<u8>> {
+        fn canonicalize(node: &mut Byml) {
+            match node {
+                Byml::Float(f) if *f == 0.0 => *f = 0.0,
+                Byml::Float(f) if f.is_nan() => *f = f32::NAN,
+                Byml::Double(d) if *d == 0.0 => *d = 0.0,
+                Byml::Double(d) if d.is_nan() => *d = f64::NAN,
+                Byml::Array(array) => array.iter_mut().for_each(canonicalize),
+                Byml::Hash(hash) => hash.values_mut().for_each(canonicalize),
+                _ => (),
+            }
+        }
+        let mut byml = self.clone();
+        canonicalize(&mut byml);
+        byml.to_binary_with_options(&Default::default())
+    }
+
+    /// Compute the exact size of the binary document that
+    /// [`Byml::to_binary_with_options`] would produce, without writing any
+    /// data.
+    pub fn compute_binary_size(&self, options: &BymlWriteOptions) -> Result<usize> {
+        let mut counter = crate::util::SizeCounter::default();
+        self.write(&mut counter, options.endian, options.version)?;
+        Ok(counter.len as usize)
+    }
 }
 
 struct NonInlineNode<'a> {
@@ -287,6 +376,11 @@ impl<'a, W: Write + Seek> WriteContext<'a, W> {
             if let Some(pos) = self.non_inline_node_data.get(&node.data).copied() {
                 self.write_at(pos, node.offset)?;
             } else {
+                // Binary data may leave the stream unaligned, but container
+                // nodes must start on a 4-byte boundary.
+                if matches!(node.data, Byml::Array(_) | Byml::Hash(_)) {
+                    self.align()?;
+                }
                 let offset = self.writer.stream_position()? as u32;
                 self.write_at(offset, node.offset)?;
                 self.non_inline_node_data.insert(node.data, offset);
@@ -332,6 +426,53 @@ impl<'a, W: Write + Seek> WriteContext<'a, W> {
 mod test {
     use super::*;
 
+    #[test]
+    fn canonical() {
+        let doc = |zero: f32, nan: f64| -> Byml {
+            [
+                ("Zero", Byml::Float(zero)),
+                ("NaN", Byml::Double(nan)),
+                ("Array", Byml::Array(vec![Byml::Float(zero)])),
+            ]
+            .into_iter()
+            .collect()
+        };
+        let positive = doc(0.0, f64::NAN);
+        let negative = doc(-0.0, -f64::NAN);
+        assert_ne!(
+            positive.to_binary(Endian::Little),
+            negative.to_binary(Endian::Little)
+        );
+        assert_eq!(
+            positive.to_binary_canonical().unwrap(),
+            negative.to_binary_canonical().unwrap()
+        );
+    }
+
+    #[test]
+    fn raw_binary_data() {
+        // Binary data is stored as raw bytes; base64 is only used for text.
+        let byml = Byml::Array(vec![Byml::BinaryData(vec![0xFF, 0x00, 0xAB])]);
+        let data = byml.to_binary(Endian::Little);
+        let expected = [3, 0, 0, 0, 0xFF, 0x00, 0xAB];
+        assert!(data.windows(expected.len()).any(|w| w == expected));
+        assert_eq!(Byml::from_binary(&data).unwrap(), byml);
+    }
+
+    #[test]
+    fn platform_options() {
+        let byml: Byml = [("A", Byml::I32(1))].into_iter().collect();
+        let wiiu = byml.to_binary_with_options(&Platform::WiiU.into()).unwrap();
+        let switch = byml
+            .to_binary_with_options(&BymlWriteOptions::for_platform(Platform::Switch))
+            .unwrap();
+        assert!(wiiu.starts_with(b"BY"));
+        assert!(switch.starts_with(b"YB"));
+        assert_eq!(wiiu.len(), switch.len());
+        assert_eq!(Byml::from_binary(&wiiu).unwrap(), byml);
+        assert_eq!(Byml::from_binary(&switch).unwrap(), byml);
+    }
+
     #[test]
     fn binary_roundtrip() {
         println!("{}", std::mem::size_of::<Hash>());
@@ -340,13 +481,37 @@ mod test {
             let bytes =
                 std::fs::read(std::path::Path::new("test/byml").join([file, ".byml"].join("")))
                     .unwrap();
+            if !cfg!(feature = "yaz0") && bytes.starts_with(b"Yaz0") {
+                continue;
+            }
             let byml = Byml::from_binary(bytes).unwrap();
-            let new_le_bytes = byml.to_binary(Endian::Little);
-            let mut new_byml = Byml::from_binary(new_le_bytes).unwrap();
-            assert_eq!(byml, new_byml);
-            let new_be_bytes = byml.to_binary(Endian::Big);
-            new_byml = Byml::from_binary(new_be_bytes).unwrap();
-            assert_eq!(byml, new_byml);
+            crate::assert_byml_roundtrip!(byml);
         }
     }
+
+    #[test]
+    fn compute_binary_size() {
+        let actor_info =
+            Byml::from_binary(std::fs::read("test/byml/ActorInfo.product.byml").unwrap()).unwrap();
+        let doc = Byml::from_iter([
+            ("Name", Byml::String("Test".into())),
+            ("Data", Byml::BinaryData(vec![1, 2, 3])),
+            ("Long", Byml::U64(1)),
+            ("List", Byml::Array(vec![Byml::Double(0.5), Byml::Null])),
+        ]);
+        for byml in [&actor_info, &doc, &Byml::Null] {
+            for (endian, version) in [(Endian::Little, 2), (Endian::Big, 3)] {
+                let options = BymlWriteOptions { endian, version };
+                assert_eq!(
+                    byml.compute_binary_size(&options).unwrap(),
+                    byml.to_binary_with_options(&options).unwrap().len()
+                );
+            }
+        }
+        assert!(
+            Byml::I32(1)
+                .compute_binary_size(&Default::default())
+                .is_err()
+        );
+    }
 }
@@ -58,6 +58,7 @@
 pub mod aamp;
 #[cfg(feature = "byml")]
 pub mod byml;
+mod provenance;
 #[cfg(feature = "sarc")]
 pub mod sarc;
 pub mod types;
@@ -66,6 +67,12 @@ mod util;
 mod yaml;
 #[cfg(feature = "yaz0")]
 pub mod yaz0;
+pub use provenance::WithProvenance;
+#[cfg(feature = "unicode-normalization")]
+pub use types::NormalizationForm;
+pub use types::{
+    Color, Curve, FixedSafeString, Quat, StringEncoding, Vector2f, Vector3f, Vector4f,
+};
 
 /// Error type for this crate.
 #[derive(Debug, thiserror::Error)]
@@ -95,6 +102,12 @@ pub enum Error {
     #[error("Parsing YAML failed: {0}")]
     InvalidYaml(#[from] ryml::Error),
     #[cfg(feature = "yaml")]
+    #[error("YAML parse error at line {0}, column {1}: {2}")]
+    YamlParseError(usize, usize, String),
+    #[cfg(feature = "yaml")]
+    #[error("Parsing YAML timed out after {0:?}")]
+    Timeout(std::time::Duration),
+    #[cfg(feature = "yaml")]
     #[error("Parsing YAML binary data failed: {0}")]
     InvalidYamlBinary(#[from] base64::DecodeError),
     #[cfg(feature = "yaz0")]
@@ -102,6 +115,10 @@ pub enum Error {
     Yaz0Error(#[from] cxx::Exception),
     #[error("{0}")]
     Any(String),
+    /// An error from loading or using a value from the given file, at the
+    /// given offset. See [`WithProvenance`].
+    #[error("In file {} at offset {:#x}: {}", .0.display(), .1, .2)]
+    InFile(std::path::PathBuf, usize, Box<Error>),
 }
 
 #[cfg_attr(feature = "sarc", binrw::binread, brw(repr = u16))]
@@ -0,0 +1,138 @@
+//! Values tagged with the file they were loaded from, for error messages in
+//! tools which process many files.
+use std::{
+    ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
+};
+
+use crate::Error;
+#[cfg(any(feature = "aamp", feature = "byml"))]
+use crate::Result;
+
+/// A value along with the path of the file it was loaded from and its byte
+/// offset in that file (0 unless it was embedded in a larger file).
+///
+/// `WithProvenance` derefs to the wrapped value, so it can be used in its
+/// place for most purposes. Use [`WithProvenance::context`] to attach the
+/// location to errors from operations on the value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WithProvenance<T> {
+    /// The wrapped value.
+    pub value: T,
+    /// Path of the file the value was loaded from.
+    pub source_path: PathBuf,
+    /// Offset of the value in the file.
+    pub byte_offset: usize,
+}
+
+impl<T> WithProvenance<T> {
+    /// Wrap a value loaded from `source_path` at `byte_offset`.
+    pub fn new(value: T, source_path: impl Into<PathBuf>, byte_offset: usize) -> Self {
+        Self {
+            value,
+            source_path: source_path.into(),
+            byte_offset,
+        }
+    }
+
+    /// Unwrap the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Wrap an error in an [`Error::InFile`] giving the location of the
+    /// value.
+    ///
+    /// ```
+    /// # use roead::{Error, WithProvenance};
+    /// let data = WithProvenance::new(vec![0u8; 4], "Actor/ActorInfo.product.byml", 0x10);
+    /// let err = data.context(Error::InvalidData("Bad data"));
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "In file Actor/ActorInfo.product.byml at offset 0x10: Bad data"
+    /// );
+    /// ```
+    pub fn context(&self, err: Error) -> Error {
+        in_file(&self.source_path, self.byte_offset, err)
+    }
+}
+
+impl<T> Deref for WithProvenance<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for WithProvenance<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.value
+    }
+}
+
+fn in_file(path: &Path, offset: usize, err: Error) -> Error {
+    Error::InFile(path.to_owned(), offset, Box::new(err))
+}
+
+/// Attach the path of a loaded file to the value or to the error.
+#[cfg(any(feature = "aamp", feature = "byml"))]
+fn with_path<T>(path: &Path, result: Result<T>) -> Result<WithProvenance<T>> {
+    result
+        .map(|value| WithProvenance::new(value, path, 0))
+        .map_err(|err| in_file(path, 0, err))
+}
+
+#[cfg(feature = "aamp")]
+impl crate::aamp::ParameterIO {
+    /// Load a binary parameter archive from a file, recording its path. Any
+    /// error is wrapped in an [`Error::InFile`].
+    pub fn from_file_provenance(
+        path: impl AsRef<Path>,
+    ) -> Result<WithProvenance<crate::aamp::ParameterIO>> {
+        let path = path.as_ref();
+        with_path(
+            path,
+            std::fs::read(path)
+                .map_err(Error::from)
+                .and_then(Self::from_binary),
+        )
+    }
+}
+
+#[cfg(feature = "byml")]
+impl crate::byml::Byml {
+    /// Load a BYML document from a file like [`Byml::from_file`], recording
+    /// its path. Any error is wrapped in an [`Error::InFile`].
+    ///
+    /// [`Byml::from_file`]: crate::byml::Byml::from_file
+    pub fn from_file_provenance(
+        path: impl AsRef<Path>,
+    ) -> Result<WithProvenance<crate::byml::Byml>> {
+        let path = path.as_ref();
+        with_path(path, Self::from_file(path))
+    }
+}
+
+#[cfg(all(test, feature = "aamp"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provenance() {
+        let pio =
+            crate::aamp::ParameterIO::from_file_provenance("test/aamp/Lizalfos.bphysics").unwrap();
+        assert_eq!(pio.source_path, Path::new("test/aamp/Lizalfos.bphysics"));
+        let data = std::fs::read("test/aamp/Lizalfos.bphysics").unwrap();
+        assert_eq!(*pio, crate::aamp::ParameterIO::from_binary(data).unwrap());
+
+        let err = crate::aamp::ParameterIO::from_file_provenance("test/aamp/missing.bphysics")
+            .unwrap_err();
+        assert!(matches!(&err, Error::InFile(path, 0, inner)
+            if path == Path::new("test/aamp/missing.bphysics") && matches!(**inner, Error::Io(_))));
+        assert!(
+            err.to_string()
+                .starts_with("In file test/aamp/missing.bphysics at offset 0x0: ")
+        );
+    }
+}
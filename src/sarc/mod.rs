@@ -44,7 +44,7 @@
 mod parse;
 mod write;
 use binrw::{binrw, BinRead, BinWrite};
-pub use parse::Sarc;
+pub use parse::{Sarc, SarcOverlap};
 pub use write::SarcWriter;
 
 use crate::Endian;
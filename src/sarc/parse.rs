@@ -127,6 +127,39 @@ impl<'a, S: std::borrow::Borrow<str>> std::ops::Index<S> for Sarc<'a> {
     }
 }
 
+/// Two files in a SARC archive with overlapping data. See
+/// [`Sarc::check_overlaps`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SarcOverlap {
+    /// Name of the file whose data starts first.
+    pub first:  std::string::String,
+    /// Name of the other file.
+    pub second: std::string::String,
+    /// The overlapping bytes, relative to the beginning of file data.
+    pub range:  std::ops::Range<usize>,
+}
+
+/// Find all pairs of overlapping `(name, begin, end)` data ranges.
+pub(super) fn find_overlaps(mut ranges: Vec<(&str, usize, usize)>) -> Vec<SarcOverlap> {
+    ranges.sort_by_key(|(_, begin, _)| *begin);
+    let mut overlaps = vec![];
+    for (i, (first, begin, end)) in ranges.iter().enumerate() {
+        for (second, other_begin, other_end) in &ranges[i + 1..] {
+            if other_begin >= end {
+                break;
+            }
+            if other_begin < other_end && begin < end {
+                overlaps.push(SarcOverlap {
+                    first:  (*first).into(),
+                    second: (*second).into(),
+                    range:  *other_begin..*end.min(other_end),
+                });
+            }
+        }
+    }
+    overlaps
+}
+
 impl<'a> Sarc<'_> {
     /// Parses a SARC archive from binary data.
     ///
@@ -212,6 +245,23 @@ impl<'a> Sarc<'_> {
         self.endian
     }
 
+    /// Get the binary data of the archive. If the archive was Yaz0
+    /// compressed, this is the decompressed data.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Serialize the archive to binary. A `Sarc` cannot be modified, so this
+    /// simply copies the data it was parsed from (after decompression). To
+    /// modify an archive, use [`SarcWriter::from_sarc`] or
+    /// [`SarcWriter::from_sarc_preserving_layout`].
+    ///
+    /// [`SarcWriter::from_sarc`]: super::SarcWriter::from_sarc
+    /// [`SarcWriter::from_sarc_preserving_layout`]: super::SarcWriter::from_sarc_preserving_layout
+    pub fn to_binary(&self) -> Vec<u8> {
+        self.data.to_vec()
+    }
+
     #[inline(always)]
     fn find_file(&self, file: &str) -> Result<Option<usize>> {
         if self.num_files == 0 {
@@ -307,6 +357,44 @@ impl<'a> Sarc<'_> {
         })
     }
 
+    /// Get the number of files that are stored in the archive. Same as
+    /// [`Sarc::len`].
+    pub fn file_count(&self) -> usize {
+        self.len()
+    }
+
+    /// Check if the archive contains a file with the given name. This uses
+    /// the name hash table, so no file names or data are read.
+    pub fn contains(&self, file: &str) -> bool {
+        matches!(self.find_file(file), Ok(Some(_)))
+    }
+
+    fn name_at(&self, index: usize) -> Option<&str> {
+        let entry_offset = self.entries_offset as usize + size_of::<ResFatEntry>() * index;
+        let entry: ResFatEntry = read(
+            self.endian,
+            &mut Cursor::new(self.data.get(entry_offset..)?),
+        )
+        .ok()?;
+        if entry.rel_name_opt_offset == 0 {
+            return None;
+        }
+        let name_offset =
+            self.names_offset as usize + (entry.rel_name_opt_offset & 0xFFFFFF) as usize * 4;
+        let name_data = self.data.get(name_offset..)?;
+        let term_pos = find_null(name_data).ok()?;
+        std::str::from_utf8(&name_data[..term_pos]).ok()
+    }
+
+    /// Returns an iterator over the names of the contained files, without
+    /// reading their data. Files without a name (or with an empty name) are
+    /// skipped.
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        (0..self.num_files as usize)
+            .filter_map(|i| self.name_at(i))
+            .filter(|name| !name.is_empty())
+    }
+
     /// Returns an iterator over the contained files
     pub fn files(&self) -> FileIterator<'_> {
         FileIterator {
@@ -340,6 +428,27 @@ impl<'a> Sarc<'_> {
         gcd as usize
     }
 
+    /// Returns the start and end of the data of each named file, relative to
+    /// the beginning of file data.
+    pub(super) fn data_ranges(&self) -> Result<Vec<(&str, usize, usize)>> {
+        let mut reader = Cursor::new(&self.data[self.entries_offset as usize..]);
+        let mut ranges = Vec::with_capacity(self.num_files as usize);
+        for file in self.files() {
+            let entry: ResFatEntry = read(self.endian, &mut reader)?;
+            if let Some(name) = file.name {
+                ranges.push((name, entry.data_begin as usize, entry.data_end as usize));
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// Find files whose data overlaps the data of another file, which only
+    /// happens in corrupted or crafted archives. This reads every file entry,
+    /// so it is not done when parsing.
+    pub fn check_overlaps(&self) -> Result<Vec<SarcOverlap>> {
+        Ok(find_overlaps(self.data_ranges()?))
+    }
+
     /// Returns true is each archive contains the same files
     pub fn are_files_equal(sarc1: &Sarc, sarc2: &Sarc) -> bool {
         if sarc1.len() != sarc2.len() {
@@ -360,6 +469,35 @@ mod tests {
     use std::fs::read;
 
     use super::*;
+
+    #[test]
+    fn check_overlaps() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert!(sarc.check_overlaps().unwrap().is_empty());
+
+        // Point the second file's data at the first file's data.
+        let entry = sarc.entries_offset as usize;
+        let mut data = data.clone();
+        let first_begin = data[entry + 8..entry + 12].to_vec();
+        data[entry + 0x10 + 8..entry + 0x10 + 12].copy_from_slice(&first_begin);
+        let sarc = Sarc::new(&data).unwrap();
+        let overlaps = sarc.check_overlaps().unwrap();
+        assert_eq!(overlaps.len(), 1);
+        let names: Vec<_> = sarc.file_names().take(2).collect();
+        assert!(names.contains(&overlaps[0].first.as_str()));
+        assert!(names.contains(&overlaps[0].second.as_str()));
+        assert!(!overlaps[0].range.is_empty());
+    }
+
+    #[test]
+    fn to_binary() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.as_bytes(), data.as_slice());
+        assert_eq!(sarc.to_binary(), data);
+    }
+
     #[test]
     fn parse_sarc() {
         let data = read("test/sarc/Dungeon119.pack").unwrap();
@@ -383,4 +521,25 @@ mod tests {
                 .unwrap_or_else(|| panic!("Could not find file {}", file));
         }
     }
+
+    #[test]
+    fn file_names() {
+        let data = read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.file_count(), 10);
+        let names: Vec<_> = sarc.file_names().collect();
+        assert_eq!(
+            names,
+            sarc.files().filter_map(|f| f.name).collect::<Vec<_>>()
+        );
+        assert!(sarc.contains("Model/DgnMrgPrt_Dungeon119.sbfres"));
+        assert!(!sarc.contains("Model/DgnMrgPrt_Dungeon120.sbfres"));
+
+        let mut writer = SarcWriter::new(Endian::Little);
+        writer.add_file("", b"unnamed".to_vec());
+        writer.add_file("A.txt", b"named".to_vec());
+        let data = writer.to_binary();
+        let sarc = Sarc::new(&data).unwrap();
+        assert_eq!(sarc.file_names().collect::<Vec<_>>(), ["A.txt"]);
+    }
 }
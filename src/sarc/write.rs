@@ -13,7 +13,7 @@ use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Deserialize;
 
 use super::*;
-use crate::{Endian, Result};
+use crate::{Endian, Error, Result};
 
 static FACTORY_INFO: &str = include_str!("../../data/botw_resource_factory_info.tsv");
 static AGLENV_INFO: &str = include_str!("../../data/aglenv_file_info.json");
@@ -85,11 +85,22 @@ pub struct SarcWriter {
     hash_multiplier: u32,
     min_alignment: usize,
     alignment_map: FxHashMap<String, usize>,
+    file_alignments: FxHashMap<String, usize>,
     options: binrw::WriteOptions,
+    layout: Option<Layout>,
+    insertion_order_preserved: bool,
+    normalize_names: bool,
     /// Files to be written.
     pub files: IndexMap<String, Vec<u8>>,
 }
 
+/// File data layout of an existing SARC archive.
+#[derive(Debug, Clone)]
+struct Layout {
+    data_offset: usize,
+    files: FxHashMap<String, (usize, usize)>,
+}
+
 impl std::fmt::Debug for SarcWriter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SarcWriter")
@@ -98,6 +109,10 @@ impl std::fmt::Debug for SarcWriter {
             .field("hash_multiplier", &self.hash_multiplier)
             .field("min_alignment", &self.min_alignment)
             .field("alignment_map", &self.alignment_map)
+            .field("file_alignments", &self.file_alignments)
+            .field("preserve_layout", &self.layout.is_some())
+            .field("insertion_order_preserved", &self.insertion_order_preserved)
+            .field("normalize_names", &self.normalize_names)
             .field("files", &self.files.keys().collect::<Vec<_>>())
             .finish()
     }
@@ -110,6 +125,9 @@ impl PartialEq for SarcWriter {
             && self.hash_multiplier == other.hash_multiplier
             && self.min_alignment == other.min_alignment
             && self.alignment_map == other.alignment_map
+            && self.file_alignments == other.file_alignments
+            && self.insertion_order_preserved == other.insertion_order_preserved
+            && self.normalize_names == other.normalize_names
             && self.files == other.files
     }
 }
@@ -124,12 +142,16 @@ impl SarcWriter {
             legacy: false,
             hash_multiplier: HASH_MULTIPLIER,
             alignment_map: FxHashMap::default(),
+            file_alignments: FxHashMap::default(),
             files: IndexMap::new(),
             options: binrw::WriteOptions::default().with_endian(match endian {
                 Endian::Big => binrw::Endian::Big,
                 Endian::Little => binrw::Endian::Little,
             }),
             min_alignment: 4,
+            layout: None,
+            insertion_order_preserved: false,
+            normalize_names: false,
         }
     }
 
@@ -142,6 +164,7 @@ impl SarcWriter {
             legacy: false,
             hash_multiplier: HASH_MULTIPLIER,
             alignment_map: FxHashMap::default(),
+            file_alignments: FxHashMap::default(),
             files: sarc
                 .files()
                 .filter_map(|f| f.name.map(|name| (name.to_string(), f.data.to_vec())))
@@ -151,9 +174,36 @@ impl SarcWriter {
                 Endian::Little => binrw::Endian::Little,
             }),
             min_alignment: sarc.guess_min_alignment(),
+            layout: None,
+            insertion_order_preserved: false,
+            normalize_names: false,
         }
     }
 
+    /// Creates a new SARC writer like [`SarcWriter::from_sarc`], but which
+    /// also records the data layout of the existing archive to keep binary
+    /// diffs minimal.
+    ///
+    /// When writing, each file that is still present and fits in its original
+    /// location (i.e. it did not grow and still satisfies its alignment
+    /// requirement) is written at its original offset. All other files are
+    /// appended after them. As a result, writing an unmodified archive
+    /// produces output identical to the original archive, and the output for
+    /// a modified archive may be slightly larger than a freshly laid out one.
+    pub fn from_sarc_preserving_layout(sarc: &Sarc) -> SarcWriter {
+        let mut writer = Self::from_sarc(sarc);
+        writer.layout = sarc.data_ranges().ok().map(|ranges| {
+            Layout {
+                data_offset: sarc.data_offset(),
+                files: ranges
+                    .into_iter()
+                    .map(|(name, begin, end)| (name.to_owned(), (begin, end)))
+                    .collect(),
+            }
+        });
+        writer
+    }
+
     /// Write a SARC archive to an in-memory buffer using the specified
     /// endianness. Default alignment requirements may be automatically
     /// added.
@@ -184,20 +234,47 @@ impl SarcWriter {
         }
         .write_options(writer, &self.options, ())?;
 
-        self.files.sort_unstable_by(|ka, _, kb, _| {
-            hash_name(HASH_MULTIPLIER, ka).cmp(&hash_name(HASH_MULTIPLIER, kb))
-        });
+        // The file table must always be sorted by name hash, as the game looks
+        // files up with a binary search. Only the order of the file data may
+        // follow insertion order.
+        if !self.insertion_order_preserved {
+            self.files.sort_unstable_by(|ka, _, kb, _| {
+                hash_name(HASH_MULTIPLIER, ka).cmp(&hash_name(HASH_MULTIPLIER, kb))
+            });
+        }
+        let mut fat_order: Vec<usize> = (0..self.files.len()).collect();
+        if self.insertion_order_preserved {
+            fat_order.sort_by_cached_key(|i| {
+                hash_name(
+                    HASH_MULTIPLIER,
+                    self.files.get_index(*i).expect("index is in range").0,
+                )
+            });
+        }
         self.add_default_alignments();
-        let mut alignments: Vec<usize> = Vec::with_capacity(self.files.len());
+        let alignments: Vec<usize> = self
+            .files
+            .iter()
+            .map(|(name, data)| self.get_alignment_for_file(name, data))
+            .collect();
+        let offsets = self.get_data_offsets(&alignments);
+        debug_assert!(
+            super::parse::find_overlaps(
+                self.files
+                    .iter()
+                    .zip(&offsets)
+                    .map(|((name, data), offset)| (name.as_str(), *offset, offset + data.len()))
+                    .collect()
+            )
+            .is_empty(),
+            "SARC writer laid out overlapping file data"
+        );
 
         {
             let mut rel_string_offset = 0;
-            let mut rel_data_offset = 0;
-            for (name, data) in self.files.iter() {
-                let alignment = self.get_alignment_for_file(name, data);
-                alignments.push(alignment);
-
-                let offset = align(rel_data_offset, alignment);
+            for &i in &fat_order {
+                let (name, data) = self.files.get_index(i).expect("index is in range");
+                let offset = offsets[i];
                 ResFatEntry {
                     name_hash: hash_name(self.hash_multiplier, name.as_ref()),
                     rel_name_opt_offset: 1 << 24 | (rel_string_offset / 4),
@@ -206,7 +283,6 @@ impl SarcWriter {
                 }
                 .write_options(writer, &self.options, ())?;
 
-                rel_data_offset = offset + data.len();
                 rel_string_offset += align(name.len() + 1, 4) as u32;
             }
         }
@@ -216,7 +292,8 @@ impl SarcWriter {
             reserved: 0,
         }
         .write_options(writer, &self.options, ())?;
-        for (name, _) in self.files.iter() {
+        for &i in &fat_order {
+            let name = self.files.get_index(i).expect("index is in range").0;
             name.as_bytes().write_options(writer, &self.options, ())?;
             0u8.write_options(writer, &self.options, ())?;
             let pos = writer.stream_position()? as usize;
@@ -227,12 +304,20 @@ impl SarcWriter {
             .iter()
             .fold(1, |acc: usize, alignment| acc.lcm(alignment));
         let pos = writer.stream_position()? as usize;
-        writer.seek(SeekFrom::Start(align(pos, required_alignment) as u64))?;
-        let data_offset_begin = writer.stream_position()? as u32;
-        for ((_, data), alignment) in self.files.iter().zip(alignments.iter()) {
-            let pos = writer.stream_position()? as usize;
-            writer.seek(SeekFrom::Start(align(pos, *alignment) as u64))?;
-            data.write_to(writer)?;
+        let data_offset_begin = match &self.layout {
+            Some(layout)
+                if layout.data_offset >= pos && layout.data_offset % required_alignment == 0 =>
+            {
+                layout.data_offset
+            }
+            _ => align(pos, required_alignment),
+        };
+        writer.seek(SeekFrom::Start(data_offset_begin as u64))?;
+        let mut order: Vec<usize> = (0..self.files.len()).collect();
+        order.sort_by_key(|i| offsets[*i]);
+        for i in order {
+            writer.seek(SeekFrom::Start((data_offset_begin + offsets[i]) as u64))?;
+            self.files[i].write_to(writer)?;
         }
 
         let file_size = writer.stream_position()? as u32;
@@ -241,7 +326,7 @@ impl SarcWriter {
             header_size: 0x14,
             bom: self.endian,
             file_size,
-            data_offset: data_offset_begin,
+            data_offset: data_offset_begin as u32,
             version: 0x0100,
             reserved: 0,
         }
@@ -249,6 +334,41 @@ impl SarcWriter {
         Ok(())
     }
 
+    /// Compute the data offset of each file relative to the beginning of file
+    /// data, keeping files at their original offsets where possible if the
+    /// writer preserves an existing layout.
+    fn get_data_offsets(&self, alignments: &[usize]) -> Vec<usize> {
+        let mut offsets = vec![None; self.files.len()];
+        let mut end = 0;
+        if let Some(layout) = &self.layout {
+            let mut used = FxHashSet::default();
+            for (((name, data), alignment), offset) in
+                self.files.iter().zip(alignments).zip(offsets.iter_mut())
+            {
+                if let Some(&(begin, original_end)) = layout.files.get(name)
+                    && data.len() <= original_end.saturating_sub(begin)
+                    && begin % alignment == 0
+                    && used.insert(begin)
+                {
+                    *offset = Some(begin);
+                    end = end.max(begin + data.len());
+                }
+            }
+        }
+        self.files
+            .values()
+            .zip(alignments)
+            .zip(offsets)
+            .map(|((data, alignment), offset)| {
+                offset.unwrap_or_else(|| {
+                    let offset = align(end, *alignment);
+                    end = offset + data.len();
+                    offset
+                })
+            })
+            .collect()
+    }
+
     /// Add or modify a data alignment requirement for a file type. Set the
     /// alignment to 1 to revert.
     ///
@@ -333,6 +453,43 @@ impl SarcWriter {
         self
     }
 
+    /// Set whether to lay out file data in the order of the `files` map
+    /// instead of sorting files by name hash.
+    ///
+    /// The file table is sorted either way, so the archive stays valid, but
+    /// the game (and [`Sarc`]) read data fastest when it is in the same order
+    /// as the table. Preserving order is useful with
+    /// [`SarcWriter::from_sarc`] to keep binary diffs small, since files
+    /// added afterwards are placed after the original files.
+    #[inline]
+    pub fn set_insertion_order_preserved(&mut self, value: bool) {
+        self.insertion_order_preserved = value
+    }
+
+    /// Builder-style method to set whether to lay out file data in insertion
+    /// order. See [`SarcWriter::set_insertion_order_preserved`].
+    #[inline]
+    pub fn with_insertion_order_preserved(mut self, value: bool) -> Self {
+        self.set_insertion_order_preserved(value);
+        self
+    }
+
+    /// Set whether to normalize the names of files added with
+    /// [`SarcWriter::add_file`] and related methods: backslashes are replaced
+    /// with forward slashes, and leading slashes are removed.
+    #[inline]
+    pub fn set_name_normalization(&mut self, value: bool) {
+        self.normalize_names = value
+    }
+
+    /// Builder-style method to set whether to normalize file names. See
+    /// [`SarcWriter::set_name_normalization`].
+    #[inline]
+    pub fn with_name_normalization(mut self, value: bool) -> Self {
+        self.set_name_normalization(value);
+        self
+    }
+
     /// Set the endianness
     #[inline]
     pub fn set_endian(&mut self, endian: Endian) {
@@ -386,6 +543,9 @@ impl SarcWriter {
 
     fn get_alignment_for_file(&self, name: impl AsRef<str>, data: &[u8]) -> usize {
         let name = name.as_ref();
+        if let Some(alignment) = self.file_alignments.get(name) {
+            return *alignment;
+        }
         let ext = match name.rfind('.') {
             Some(idx) => &name[idx + 1..],
             None => "",
@@ -406,11 +566,90 @@ impl SarcWriter {
         alignment
     }
 
+    fn prepare_name(&self, name: impl Into<String>) -> String {
+        let name = name.into();
+        if self.normalize_names {
+            name.replace('\\', "/").trim_start_matches('/').to_owned()
+        } else {
+            name
+        }
+    }
+
+    /// Check that a file name can be stored in a SARC archive and found by
+    /// the game: it must not be empty, contain NUL bytes or backslashes, or
+    /// start with a slash.
+    pub fn validate_file_name(name: &str) -> Result<()> {
+        let reason = if name.is_empty() {
+            "name is empty"
+        } else if name.contains('\0') {
+            "name contains a NUL byte"
+        } else if name.contains('\\') {
+            "name contains a backslash (use / to separate directories)"
+        } else if name.starts_with('/') {
+            "name starts with a slash"
+        } else {
+            return Ok(());
+        };
+        Err(Error::InvalidDataD(format!(
+            "Invalid SARC file name {name:?}: {reason}"
+        )))
+    }
+
     /// Add a file to the archive, with greater generic flexibility than using
     /// `insert` on the `files` field.
+    ///
+    /// The name is not checked; see [`SarcWriter::try_add_file`].
     #[inline]
     pub fn add_file(&mut self, name: impl Into<String>, data: impl Into<Vec<u8>>) {
-        self.files.insert(name.into(), data.into());
+        let name = self.prepare_name(name);
+        self.file_alignments.remove(&name);
+        self.files.insert(name, data.into());
+    }
+
+    /// Add a file to the archive, first checking its name (after
+    /// normalization, if enabled) with [`SarcWriter::validate_file_name`].
+    pub fn try_add_file(
+        &mut self,
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Result<()> {
+        let name = self.prepare_name(name);
+        Self::validate_file_name(&name)?;
+        self.add_file(name, data);
+        Ok(())
+    }
+
+    /// Add a file to the archive with its own data alignment, which is used
+    /// instead of the alignment that would otherwise be determined from the
+    /// minimum alignment, its file type, and its contents.
+    ///
+    /// Panics if the alignment is not a power of 2 or is greater than
+    /// 0x10000.
+    pub fn add_file_with_alignment(
+        &mut self,
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+        alignment: usize,
+    ) {
+        if !is_valid_alignment(alignment) || alignment > 0x10000 {
+            panic!("Invalid file alignment");
+        }
+        let name = self.prepare_name(name);
+        self.files.insert(name.clone(), data.into());
+        self.file_alignments.insert(name, alignment);
+    }
+
+    /// Builder-style method to add a file to the archive with its own data
+    /// alignment. See [`SarcWriter::add_file_with_alignment`].
+    #[inline]
+    pub fn with_file_aligned(
+        mut self,
+        name: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+        alignment: usize,
+    ) -> Self {
+        self.add_file_with_alignment(name, data, alignment);
+        self
     }
 
     /// Builder-style method to add a file to the archive.
@@ -428,10 +667,9 @@ impl SarcWriter {
         N: Into<String>,
         D: Into<Vec<u8>>,
     {
-        self.files.extend(
-            iter.into_iter()
-                .map(|(name, data)| (name.into(), data.into())),
-        );
+        for (name, data) in iter {
+            self.add_file(name, data);
+        }
     }
 
     /// Builder-style method to add files to the archive from an iterator.
@@ -452,6 +690,18 @@ impl SarcWriter {
         String: Borrow<Q>,
     {
         self.files.remove(name);
+        self.file_alignments.remove(name);
+    }
+
+    /// Reserve capacity for at least `additional` more files, e.g. before
+    /// adding a known number of files.
+    pub fn reserve(&mut self, additional: usize) {
+        self.files.reserve(additional);
+    }
+
+    /// The number of files added so far.
+    pub fn file_count(&self) -> usize {
+        self.files.len()
     }
 
     /// Get a file's data from the archive, for convience.
@@ -512,4 +762,133 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn preserve_layout() {
+        for file in [
+            "ActorObserverByActorTagTag.sarc",
+            "test.sarc",
+            "A-1.00.sarc",
+            "Common.blarc",
+        ] {
+            let data = std::fs::read(std::path::Path::new("test/sarc").join(file)).unwrap();
+            let sarc = Sarc::new(&data).unwrap();
+            let new_data = SarcWriter::from_sarc_preserving_layout(&sarc).to_binary();
+            assert!(
+                data == new_data,
+                "Roundtrip not binary identical for {file}"
+            );
+        }
+
+        let data = std::fs::read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let mut names = sarc.files().map(|f| f.unwrap_name().to_owned());
+        let (shrunk, grown) = (names.next().unwrap(), names.next().unwrap());
+        let mut writer = SarcWriter::from_sarc_preserving_layout(&sarc);
+        writer.files[shrunk.as_str()].truncate(4);
+        writer.files[grown.as_str()].extend_from_slice(&[0; 0x100]);
+        let new_data = writer.to_binary();
+        let new_sarc = Sarc::new(&new_data).unwrap();
+        assert_eq!(new_sarc.data_offset(), sarc.data_offset());
+        let ranges = sarc.data_ranges().unwrap();
+        let new_ranges = new_sarc.data_ranges().unwrap();
+        for ((name, begin, end), (new_name, new_begin, new_end)) in ranges.iter().zip(&new_ranges) {
+            assert_eq!(name, new_name);
+            if *name == grown {
+                assert!(*new_begin >= ranges.iter().map(|r| r.2).max().unwrap());
+            } else if *name == shrunk {
+                assert_eq!((*new_begin, *new_end), (*begin, begin + 4));
+            } else {
+                assert_eq!((new_begin, new_end), (begin, end));
+            }
+            assert_eq!(
+                new_sarc.get_data(name),
+                writer.get_file(*name).map(|d| d.as_slice())
+            );
+        }
+    }
+
+    #[test]
+    fn insertion_order() {
+        let data = std::fs::read("test/sarc/Dungeon119.pack").unwrap();
+        let sarc = Sarc::new(&data).unwrap();
+        let mut writer = SarcWriter::from_sarc(&sarc).with_insertion_order_preserved(true);
+        writer.add_file("Actor/AAA_Added.bin", vec![1; 0x10]);
+        let new_data = writer.to_binary();
+        let new_sarc = Sarc::new(&new_data).unwrap();
+        assert_eq!(new_sarc.len(), sarc.len() + 1);
+        for file in sarc.files() {
+            assert_eq!(new_sarc.get_data(file.unwrap_name()), Some(file.data));
+        }
+        let by_offset = |sarc: &Sarc| {
+            let mut ranges = sarc.data_ranges().unwrap();
+            ranges.sort_by_key(|(_, begin, _)| *begin);
+            ranges
+                .into_iter()
+                .map(|(name, ..)| name.to_owned())
+                .collect::<Vec<_>>()
+        };
+        let mut expected = by_offset(&sarc);
+        expected.push("Actor/AAA_Added.bin".to_owned());
+        assert_eq!(by_offset(&new_sarc), expected);
+    }
+
+    #[test]
+    fn file_names() {
+        let mut writer = SarcWriter::new(crate::Endian::Little);
+        assert!(
+            writer
+                .try_add_file("Actor/Pack/A.bactorpack", vec![])
+                .is_ok()
+        );
+        for name in ["", "A\0B", "Actor\\A.bxml", "/Actor/A.bxml"] {
+            assert!(writer.try_add_file(name, vec![]).is_err(), "{name:?}");
+        }
+        writer.set_name_normalization(true);
+        writer.try_add_file("\\Actor\\B.bxml", vec![1]).unwrap();
+        writer.add_file("//Actor/C.bxml", vec![2]);
+        assert_eq!(writer.get_file("Actor/B.bxml"), Some(&vec![1]));
+        assert_eq!(writer.get_file("Actor/C.bxml"), Some(&vec![2]));
+        assert_eq!(writer.file_count(), 3);
+    }
+
+    #[test]
+    fn reserve() {
+        let mut writer = SarcWriter::new(crate::Endian::Little);
+        writer.reserve(100);
+        assert!(writer.files.capacity() >= 100);
+        assert_eq!(writer.file_count(), 0);
+        writer.add_file("A", b"A".as_slice());
+        writer.add_file("B", b"B".as_slice());
+        assert_eq!(writer.file_count(), 2);
+    }
+
+    #[test]
+    fn file_alignment() {
+        let mut writer = SarcWriter::new(crate::Endian::Little)
+            .with_file("A.byml", vec![1; 3])
+            .with_file_aligned("B.bfres", vec![2; 5], 0x1000)
+            .with_file_aligned("C.bin", vec![3; 7], 0x100);
+        writer.add_file("C.bin", vec![3; 7]);
+        let data = writer.to_binary();
+        let sarc = Sarc::new(&data).unwrap();
+        let begin = |name| {
+            let (_, begin, _) = sarc
+                .data_ranges()
+                .unwrap()
+                .into_iter()
+                .find(|(n, ..)| *n == name)
+                .unwrap();
+            sarc.data_offset() + begin
+        };
+        assert_eq!(begin("B.bfres") % 0x1000, 0);
+        assert_ne!(begin("C.bin") % 0x100, 0);
+        assert_eq!(sarc.get_data("B.bfres"), Some([2; 5].as_slice()));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_file_alignment() {
+        SarcWriter::new(crate::Endian::Little).add_file_with_alignment("A", vec![], 0x20000);
+    }
 }
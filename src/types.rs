@@ -1,5 +1,21 @@
 #![allow(clippy::derived_hash_with_manual_eq)]
 //! Miscellaneous needful oead types.
+//!
+//! These are the value types shared by the format modules. All of them are
+//! also re-exported at the crate root.
+//!
+//! - [`Vector2f`], [`Vector3f`], [`Vector4f`], [`Quat`] and [`Color`] are the values of the AAMP
+//!   parameters of the same names, e.g. `Parameter::Vec3(Vector3f)` and `Parameter::Color(Color)`.
+//! - [`Curve`] is an AAMP curve; `Parameter::Curve1` through `Parameter::Curve4` hold arrays of one
+//!   to four of them.
+//! - [`FixedSafeString`] is a string with inline storage, used for the fixed-size AAMP string
+//!   parameters `String32`, `String64` and `String256`. Strings which are too long are truncated.
+//! - [`StringEncoding`] selects how strings in binary AAMP and BYML files are decoded, and
+//!   [`NormalizationForm`] (with the `unicode-normalization` feature) how parsed strings are
+//!   normalized.
+//!
+//! BYML nodes only contain scalars, strings, binary data, arrays and hashes,
+//! so none of the vector types appear in [`Byml`](crate::byml::Byml).
 // use decorum::f32;
 #[cfg(feature = "with-serde")]
 use serde::{Deserialize, Serialize};
@@ -18,7 +34,7 @@ use serde::{Deserialize, Serialize};
     derive(Serialize, Deserialize),
     serde(from = "std::string::String", into = "std::string::String")
 )]
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
+#[derive(Clone, Copy)]
 pub struct FixedSafeString<const N: usize> {
     data: [u8; N],
     len:  usize,
@@ -33,6 +49,35 @@ impl<const N: usize> Default for FixedSafeString<N> {
     }
 }
 
+// Comparisons and hashing only look at the string content, consistent with
+// `str` (as required by the `Borrow<str>` implementation), and not at the
+// unused part of the buffer.
+impl<const N: usize> PartialEq for FixedSafeString<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for FixedSafeString<N> {}
+
+impl<const N: usize> PartialOrd for FixedSafeString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for FixedSafeString<N> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> std::hash::Hash for FixedSafeString<N> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
 impl<const N: usize> std::fmt::Debug for FixedSafeString<N> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         self.as_ref().fmt(f)
@@ -326,3 +371,95 @@ impl std::hash::Hash for Curve {
         }
     }
 }
+
+/// Encoding of strings in binary files.
+///
+/// Variants depend on the enabled features, so matches on this enum outside
+/// roead need a wildcard arm.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StringEncoding {
+    /// UTF-8, as used by BOTW and most newer games.
+    #[default]
+    Utf8,
+    /// Shift-JIS, as used by some older (e.g. 3DS) games.
+    ///
+    /// This is only available with the `shift-jis` feature.
+    #[cfg(feature = "shift-jis")]
+    ShiftJis,
+}
+
+impl StringEncoding {
+    /// Decode a string from raw bytes (without a null terminator).
+    pub fn decode(&self, bytes: &[u8]) -> crate::Result<smartstring::alias::String> {
+        match self {
+            StringEncoding::Utf8 => Ok(std::str::from_utf8(bytes)?.into()),
+            #[cfg(feature = "shift-jis")]
+            StringEncoding::ShiftJis => {
+                encoding_rs::SHIFT_JIS
+                    .decode_without_bom_handling_and_without_replacement(bytes)
+                    .map(|s| s.as_ref().into())
+                    .ok_or(crate::Error::InvalidData("Invalid Shift-JIS string"))
+            }
+        }
+    }
+}
+
+/// Unicode normalization form to apply to strings.
+///
+/// This is only available with the `unicode-normalization` feature.
+#[cfg(feature = "unicode-normalization")]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// Leave strings as they are.
+    #[default]
+    None,
+    /// Canonical composition (NFC).
+    Nfc,
+    /// Canonical decomposition (NFD).
+    Nfd,
+}
+
+#[cfg(feature = "unicode-normalization")]
+impl NormalizationForm {
+    /// Normalize a string, borrowing it if it is already in the target form.
+    pub fn normalize<'a>(&self, s: &'a str) -> std::borrow::Cow<'a, str> {
+        use unicode_normalization::{
+            is_nfc_quick, is_nfd_quick, IsNormalized, UnicodeNormalization,
+        };
+        match self {
+            NormalizationForm::None => s.into(),
+            NormalizationForm::Nfc => {
+                if is_nfc_quick(s.chars()) == IsNormalized::Yes {
+                    s.into()
+                } else {
+                    s.nfc().collect::<std::string::String>().into()
+                }
+            }
+            NormalizationForm::Nfd => {
+                if is_nfd_quick(s.chars()) == IsNormalized::Yes {
+                    s.into()
+                } else {
+                    s.nfd().collect::<std::string::String>().into()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_safe_string_ord_hash() {
+        let mut strings: Vec<FixedSafeString<32>> =
+            ["b", "ab", "a"].into_iter().map(Into::into).collect();
+        strings.sort();
+        assert_eq!(strings, ["a", "ab", "b"].map(FixedSafeString::from));
+
+        let set: std::collections::HashSet<FixedSafeString<32>> = strings.into_iter().collect();
+        assert!(set.contains("ab"));
+        assert!(!set.contains("c"));
+    }
+}
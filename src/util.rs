@@ -3,6 +3,258 @@ pub(crate) fn align(value: u32, size: u32) -> u32 {
     value + (size - value % size) % size
 }
 
+/// A writer which discards all data, only keeping track of the size of the
+/// output.
+#[derive(Debug, Default)]
+pub(crate) struct SizeCounter {
+    pos: u64,
+    pub(crate) len: u64,
+}
+
+impl std::io::Write for SizeCounter {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pos += buf.len() as u64;
+        self.len = self.len.max(self.pos);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl std::io::Seek for SizeCounter {
+    fn seek(&mut self, pos: std::io::SeekFrom) -> std::io::Result<u64> {
+        let new_pos = match pos {
+            std::io::SeekFrom::Start(offset) => Some(offset),
+            std::io::SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+            std::io::SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+        };
+        self.pos = new_pos.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Invalid seek to a negative position",
+            )
+        })?;
+        Ok(self.pos)
+    }
+}
+
+/// Give up looking for common elements after this many differences, treating
+/// the rest of the sequences as entirely changed. This bounds the work done by
+/// [`common_subsequence`] to O((N + M) · D) time and O(D²) memory.
+#[cfg(any(feature = "byml", all(feature = "aamp", feature = "yaml")))]
+const MAX_EDIT_DISTANCE: usize = 2000;
+
+/// Find the elements common to `a` and `b` using Myers' diff algorithm,
+/// returning pairs of matching indices in increasing order. The common prefix
+/// and suffix are split off first so that the search only has to deal with
+/// the changed region. If that region needs more than [`MAX_EDIT_DISTANCE`]
+/// edits, only the prefix and suffix are matched.
+#[cfg(any(feature = "byml", all(feature = "aamp", feature = "yaml")))]
+pub(crate) fn common_subsequence<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut matches: Vec<(usize, usize)> = (0..prefix).map(|i| (i, i)).collect();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+    matches.extend(
+        myers(a_mid, b_mid)
+            .into_iter()
+            .map(|(x, y)| (x + prefix, y + prefix)),
+    );
+    matches.extend((0..suffix).map(|i| (a.len() - suffix + i, b.len() - suffix + i)));
+    matches
+}
+
+#[cfg(any(feature = "byml", all(feature = "aamp", feature = "yaml")))]
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = (a.len() + b.len()).min(MAX_EDIT_DISTANCE) as isize;
+    let offset = max + 1;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // Step d only ever reads back diagonals -d - 1..=d + 1, so only that
+    // window of `v` is kept for each step.
+    let mut trace: Vec<Vec<isize>> = vec![];
+    let mut found = n == 0 && m == 0;
+    'outer: for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        let mut k = -d;
+        while k <= d {
+            let idx = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                found = true;
+                break 'outer;
+            }
+            k += 2;
+        }
+    }
+    if !found {
+        return vec![];
+    }
+    // Walk the trace backwards to recover the matching diagonals.
+    let mut matches = vec![];
+    let (mut x, mut y) = (n, m);
+    for (d, window) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let v = |k: isize| window[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v(k - 1) < v(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = v(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        if d > 0 {
+            x = prev_x;
+            y = prev_y;
+        }
+    }
+    matches.reverse();
+    matches
+}
+
+/// Hit and miss counts of a [`ParameterIOCache`](crate::aamp::ParameterIOCache)
+/// or [`BymlCache`](crate::byml::BymlCache).
+#[cfg(any(feature = "aamp", feature = "byml"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of lookups answered from the cache.
+    pub hits:   u64,
+    /// Number of lookups which required parsing.
+    pub misses: u64,
+}
+
+/// Minimum number of entries before dead entries are pruned.
+#[cfg(any(feature = "aamp", feature = "byml"))]
+pub(crate) const MIN_PRUNE_THRESHOLD: usize = 64;
+
+#[cfg(any(feature = "aamp", feature = "byml"))]
+#[derive(Debug)]
+struct ContentCacheEntries<T> {
+    map: rustc_hash::FxHashMap<(u64, usize), std::sync::Weak<T>>,
+    /// Dead entries are only pruned once the map reaches this size, which is
+    /// then set to twice the number of live entries, so pruning costs
+    /// amortized constant time per insert.
+    prune_threshold: usize,
+}
+
+/// Thread-safe cache of parsed documents keyed by a hash of their binary
+/// contents, shared by the AAMP and BYML caches. Only weak references are
+/// held, so documents are dropped as soon as the last [`Arc`] for them goes
+/// away. Entries are matched by a 64-bit content hash plus the data length.
+///
+/// [`Arc`]: std::sync::Arc
+#[cfg(any(feature = "aamp", feature = "byml"))]
+#[derive(Debug)]
+pub(crate) struct ContentCache<T> {
+    entries: parking_lot::Mutex<ContentCacheEntries<T>>,
+    hits: std::sync::atomic::AtomicU64,
+    misses: std::sync::atomic::AtomicU64,
+}
+
+#[cfg(any(feature = "aamp", feature = "byml"))]
+impl<T> Default for ContentCache<T> {
+    fn default() -> Self {
+        Self {
+            entries: parking_lot::Mutex::new(ContentCacheEntries {
+                map: Default::default(),
+                prune_threshold: MIN_PRUNE_THRESHOLD,
+            }),
+            hits: Default::default(),
+            misses: Default::default(),
+        }
+    }
+}
+
+#[cfg(any(feature = "aamp", feature = "byml"))]
+impl<T> ContentCache<T> {
+    fn key(data: &[u8]) -> (u64, usize) {
+        use std::hash::Hasher;
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        hasher.write(data);
+        (hasher.finish(), data.len())
+    }
+
+    pub(crate) fn get_or_parse(
+        &self,
+        data: &[u8],
+        parse: impl FnOnce(&[u8]) -> crate::Result<T>,
+    ) -> crate::Result<std::sync::Arc<T>> {
+        use std::sync::{atomic::Ordering, Arc, Weak};
+        let key = Self::key(data);
+        if let Some(doc) = self.entries.lock().map.get(&key).and_then(Weak::upgrade) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(doc);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let doc = Arc::new(parse(data)?);
+        let mut entries = self.entries.lock();
+        // Another thread may have parsed the same data in the meantime.
+        if let Some(existing) = entries.map.get(&key).and_then(Weak::upgrade) {
+            return Ok(existing);
+        }
+        if entries.map.len() >= entries.prune_threshold {
+            entries.map.retain(|_, doc| doc.strong_count() > 0);
+            entries.prune_threshold = (entries.map.len() * 2).max(MIN_PRUNE_THRESHOLD);
+        }
+        entries.map.insert(key, Arc::downgrade(&doc));
+        Ok(doc)
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        use std::sync::atomic::Ordering;
+        CacheStats {
+            hits:   self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .map
+            .values()
+            .filter(|doc| doc.strong_count() > 0)
+            .count()
+    }
+
+    /// Number of map entries, including dead ones which have not been pruned.
+    #[cfg(all(test, feature = "aamp"))]
+    pub(crate) fn raw_len(&self) -> usize {
+        self.entries.lock().map.len()
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut entries = self.entries.lock();
+        entries.map.clear();
+        entries.prune_threshold = MIN_PRUNE_THRESHOLD;
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct u24(pub u32);
@@ -108,3 +360,132 @@ fn test_u24_rw() {
     .unwrap();
     assert_eq!(num.0, 8388608);
 }
+
+/// Describe where two parameter IOs first differ, for round trip assertions.
+#[cfg(all(test, feature = "aamp"))]
+pub(crate) fn aamp_difference(
+    left: &crate::aamp::ParameterIO,
+    right: &crate::aamp::ParameterIO,
+) -> std::string::String {
+    if left.version != right.version || left.data_type != right.data_type {
+        return "version or data type differs".into();
+    }
+    let mut left_objs = left.iter_objects();
+    let mut right_objs = right.iter_objects();
+    loop {
+        match (left_objs.next(), right_objs.next()) {
+            (Some(l), Some(r)) if l == r => continue,
+            (Some((path, _)), _) | (None, Some((path, _))) => {
+                let path: Vec<_> = path.iter().map(|name| name.to_string()).collect();
+                return format!("first difference at object {}", path.join("/"));
+            }
+            (None, None) => return "objects match, list structure differs".into(),
+        }
+    }
+}
+
+/// Describe where two BYML documents first differ, for round trip
+/// assertions.
+#[cfg(all(test, feature = "byml"))]
+pub(crate) fn byml_difference(
+    left: &crate::byml::Byml,
+    right: &crate::byml::Byml,
+) -> std::string::String {
+    use crate::byml::Byml;
+    fn find(left: &Byml, right: &Byml, path: &mut Vec<std::string::String>) -> bool {
+        match (left, right) {
+            (Byml::Array(l), Byml::Array(r)) if l.len() == r.len() => {
+                l.iter().zip(r).enumerate().any(|(i, (l, r))| {
+                    path.push(i.to_string());
+                    let found = find(l, r, path);
+                    if !found {
+                        path.pop();
+                    }
+                    found
+                })
+            }
+            (Byml::Hash(l), Byml::Hash(r)) if l.len() == r.len() => {
+                let mut keys: Vec<_> = l.keys().collect();
+                keys.sort();
+                keys.into_iter().any(|key| {
+                    path.push(key.to_string());
+                    let found = r.get(key).map_or(true, |r| find(&l[key], r, path));
+                    if !found {
+                        path.pop();
+                    }
+                    found
+                })
+            }
+            _ => left != right,
+        }
+    }
+    let mut path = vec![];
+    find(left, right, &mut path);
+    format!("first difference at node /{}", path.join("/"))
+}
+
+/// Assert that writing a [`ParameterIO`](crate::aamp::ParameterIO) to binary
+/// and parsing it back yields the same parameter IO.
+#[cfg(all(test, feature = "aamp"))]
+#[macro_export]
+macro_rules! assert_aamp_roundtrip {
+    ($pio:expr) => {{
+        let pio: &$crate::aamp::ParameterIO = &$pio;
+        let roundtripped = $crate::aamp::ParameterIO::from_binary(pio.to_binary())
+            .expect("Failed to parse written parameter archive");
+        assert!(
+            *pio == roundtripped,
+            "Parameter IO changed in binary round trip: {}",
+            $crate::util::aamp_difference(pio, &roundtripped)
+        );
+    }};
+}
+
+/// Assert that writing a [`Byml`](crate::byml::Byml) document to binary and
+/// parsing it back yields the same document, in both endiannesses unless one
+/// is given.
+#[cfg(all(test, feature = "byml"))]
+#[macro_export]
+macro_rules! assert_byml_roundtrip {
+    ($byml:expr) => {{
+        let byml: &$crate::byml::Byml = &$byml;
+        $crate::assert_byml_roundtrip!(byml, $crate::Endian::Little);
+        $crate::assert_byml_roundtrip!(byml, $crate::Endian::Big);
+    }};
+    ($byml:expr, $endian:expr) => {{
+        let byml: &$crate::byml::Byml = &$byml;
+        let endian = $endian;
+        let roundtripped = $crate::byml::Byml::from_binary(byml.to_binary(endian))
+            .expect("Failed to parse written BYML document");
+        assert!(
+            *byml == roundtripped,
+            "BYML document changed in {:?} binary round trip: {}",
+            endian,
+            $crate::util::byml_difference(byml, &roundtripped)
+        );
+    }};
+}
+
+#[cfg(all(test, feature = "aamp", feature = "byml"))]
+#[test]
+fn roundtrip_differences() {
+    use crate::{aamp::*, byml::Byml};
+    let obj = |v| ParameterObject::new().with_parameter("V", Parameter::I32(v));
+    let left = ParameterIO::new().with_list("L", ParameterList::new().with_object("A", obj(1)));
+    let right = ParameterIO::new().with_list("L", ParameterList::new().with_object("A", obj(2)));
+    assert_eq!(
+        aamp_difference(&left, &right),
+        format!(
+            "first difference at object {}/{}",
+            Name::from("L"),
+            Name::from("A")
+        )
+    );
+
+    let left = Byml::from_iter([("a", Byml::Array(vec![Byml::I32(1), Byml::I32(2)]))]);
+    let right = Byml::from_iter([("a", Byml::Array(vec![Byml::I32(1), Byml::I32(3)]))]);
+    assert_eq!(
+        byml_difference(&left, &right),
+        "first difference at node /a/1"
+    );
+}
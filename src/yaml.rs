@@ -49,6 +49,17 @@ fn in_nan(input: &str) -> bool {
     matches!(input, ".nan" | ".NaN" | ".NAN")
 }
 
+/// The boolean forms of the YAML 1.1 type repository, as accepted by most
+/// YAML 1.1 parsers (the single-letter `y` and `n` forms are not included).
+#[inline]
+pub(crate) fn parse_yaml_1_1_bool(value: &str) -> Option<bool> {
+    match value {
+        "yes" | "Yes" | "YES" | "true" | "True" | "TRUE" | "on" | "On" | "ON" => Some(true),
+        "no" | "No" | "NO" | "false" | "False" | "FALSE" | "off" | "Off" | "OFF" => Some(false),
+        _ => None,
+    }
+}
+
 /// Deliberately not compliant to the YAML 1.2 standard to get rid of unused
 /// features that harm performance.
 #[inline]
@@ -57,7 +68,9 @@ pub(crate) fn parse_scalar(
     value: &str,
     is_quoted: bool,
 ) -> Result<Scalar> {
-    if tag_type == Some(TagBasedType::Bool) || matches!(value, "true" | "false") {
+    if tag_type == Some(TagBasedType::Bool)
+        || (tag_type.is_none() && !is_quoted && matches!(value, "true" | "false"))
+    {
         Ok(Scalar::Bool(&value[..1] == "t"))
     } else {
         // Floating-point conversions.
@@ -114,7 +127,9 @@ pub(crate) fn parse_scalar(
                 }
             }
         }
-        if tag_type == Some(TagBasedType::Null) || matches!(value, "null" | "~" | "NULL" | "Null") {
+        if tag_type == Some(TagBasedType::Null)
+            || (tag_type.is_none() && !is_quoted && matches!(value, "null" | "~" | "NULL" | "Null"))
+        {
             Ok(Scalar::Null)
         } else {
             // Fall back to treating the value as a string.
@@ -123,9 +138,57 @@ pub(crate) fn parse_scalar(
     }
 }
 
+/// Parse a YAML document, converting any rapidyaml parse error into an
+/// [`Error::YamlParseError`] with the line and column of the problem.
+pub(crate) fn parse_yaml(text: &str) -> Result<ryml::Tree<'static>> {
+    ryml::Tree::parse(text).map_err(|e| locate_yaml_error(text, e))
+}
+
+/// rapidyaml reports errors as a message, optionally followed by a
+/// `line:col: <source line>` excerpt and an `at :line` trailer. When only the
+/// line is known, the column of the first non-blank character on that line is
+/// used as an approximation.
+fn locate_yaml_error(text: &str, error: ryml::Error) -> Error {
+    let ryml::Error::Other(exception) = &error else {
+        return Error::InvalidYaml(error);
+    };
+    let what = exception.what();
+    let mut lines = what.lines();
+    let message = lines
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("ERROR: ")
+        .trim();
+    let excerpt = lines.clone().find_map(|line| {
+        let mut parts = line.splitn(3, ':');
+        let line = parts.next()?.trim().parse::<usize>().ok()?;
+        let column = parts.next()?.trim().parse::<usize>().ok()?;
+        Some((line, column))
+    });
+    let location = excerpt.or_else(|| {
+        let line = lines
+            .find_map(|l| l.trim().strip_prefix("at :"))
+            .and_then(|l| l.trim().parse::<usize>().ok())?
+            // Errors at the end of the input are reported one line past it.
+            .clamp(1, text.lines().count().max(1));
+        let column = text
+            .lines()
+            .nth(line.saturating_sub(1))
+            .map(|l| l.len() - l.trim_start().len() + 1)
+            .unwrap_or(1);
+        Some((line, column))
+    });
+    match location {
+        Some((line, column)) => Error::YamlParseError(line, column, message.into()),
+        None => Error::InvalidYaml(error),
+    }
+}
+
 #[inline]
 pub(crate) fn string_needs_quotes(value: &str) -> bool {
-    matches!(value, "true" | "false")
+    // YAML 1.1 booleans are quoted too, so that the output means the same
+    // to YAML 1.1 parsers.
+    parse_yaml_1_1_bool(value).is_some()
         || value.starts_with('!')
         || (value.contains('.')
             && (is_infinity(value)
@@ -149,3 +212,24 @@ macro_rules! format_hex {
     };
 }
 pub(crate) use format_hex;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_error_location() {
+        let text = "a: 1\n  b: 2\n - c\n";
+        match parse_yaml(text) {
+            Err(Error::YamlParseError(line, column, message)) => {
+                assert_eq!((line, column), (2, 4));
+                assert!(!message.is_empty());
+            }
+            _ => panic!("Expected YAML parse error"),
+        }
+        match parse_yaml("a: 1\nkey: 'abc\n") {
+            Err(Error::YamlParseError(line, ..)) => assert_eq!(line, 2),
+            _ => panic!("Expected YAML parse error"),
+        }
+    }
+}